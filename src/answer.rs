@@ -0,0 +1,145 @@
+//! The answers that puzzle parts produce.
+
+use std::fmt;
+
+/// An answer to one part of a puzzle.
+///
+/// Most answers are a single number, but some puzzles ask for text, several
+/// values or a picture drawn in characters. Keeping the shape lets the runner
+/// print, serialise and check each kind in a way that suits it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Answer {
+    Integer(i128),
+    String(String),
+    List(Vec<Answer>),
+    /// Rows of characters, such as letters drawn in `#` and `.`.
+    Grid(Vec<String>),
+}
+
+impl Answer {
+    /// A grid from its rows.
+    pub fn grid<I, S>(rows: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::Grid(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Whether this matches `expected`, an answer written down as text.
+    ///
+    /// Integers compare by value, lists item by item separated by commas,
+    /// and grids row by row ignoring trailing whitespace.
+    pub fn matches(&self, expected: &str) -> bool {
+        match self {
+            Self::Integer(n) => expected.trim().parse::<i128>() == Ok(*n),
+            Self::String(s) => s == expected.trim(),
+            Self::List(items) => {
+                let expected: Vec<_> = expected.split(',').collect();
+
+                expected.len() == items.len()
+                    && items
+                        .iter()
+                        .zip(expected)
+                        .all(|(item, expected)| item.matches(expected))
+            }
+            Self::Grid(rows) => {
+                let expected: Vec<_> = expected.trim_matches('\n').lines().collect();
+
+                expected.len() == rows.len()
+                    && rows
+                        .iter()
+                        .zip(expected)
+                        .all(|(row, expected)| row.trim_end() == expected.trim_end())
+            }
+        }
+    }
+
+    /// Whether the answer takes more than one line to write out.
+    pub fn is_multiline(&self) -> bool {
+        match self {
+            Self::Grid(rows) => rows.len() > 1,
+            answer => answer.to_string().contains('\n'),
+        }
+    }
+}
+
+macro_rules! from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Self::Integer(n as i128)
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(i32, i64, i128, u32, u64, usize);
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Self::String(String::from(s))
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl<T: Into<Answer>> From<Vec<T>> for Answer {
+    fn from(items: Vec<T>) -> Self {
+        Self::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integer(n) => write!(f, "{}", n),
+            Self::String(s) => f.write_str(s),
+            Self::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+
+                    write!(f, "{}", item)?;
+                }
+
+                Ok(())
+            }
+            Self::Grid(rows) => f.write_str(&rows.join("\n")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Answer::from(-12_i64).to_string(), "-12");
+        assert_eq!(Answer::from(vec![3, 4, 5]).to_string(), "3,4,5");
+        assert_eq!(Answer::grid(vec!["#.", ".#"]).to_string(), "#.\n.#");
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(Answer::from(514_579).matches(" 514579"));
+        assert!(!Answer::from(514_579).matches("514578"));
+
+        assert!(Answer::from("abc").matches("abc"));
+
+        assert!(Answer::from(vec![3, 4, 5]).matches("3, 4,5"));
+        assert!(!Answer::from(vec![3, 4]).matches("3,4,5"));
+
+        let grid = Answer::grid(vec!["#..#", "#..#  "]);
+        assert!(grid.matches("#..#\n#..#\n"));
+        assert!(!grid.matches("#..#"));
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use advent_of_code_2020_rs::answer::Answer;
+use anyhow::{anyhow, bail};
+
+/// Known correct answers for a day, read from a `dayN.answers` file next to
+/// the input.
+///
+/// Each non-empty line holds a part number and its answer, e.g. `1: 514579`.
+/// Lines starting with `#` are ignored. An answer over several lines, such as
+/// a grid, follows its part with one `|`-prefixed line per row:
+///
+/// ```text
+/// 2:
+///   |#..#
+///   |####
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers {
+    parts: BTreeMap<u32, String>,
+}
+
+impl FromStr for Answers {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: BTreeMap<u32, String> = BTreeMap::new();
+        let mut last_part = None;
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(row) = line.strip_prefix('|') {
+                let answer = last_part
+                    .and_then(|part| parts.get_mut(&part))
+                    .ok_or_else(|| anyhow!("Line {}: row before any part", i + 1))?;
+
+                if !answer.is_empty() {
+                    answer.push('\n');
+                }
+                answer.push_str(row);
+
+                continue;
+            }
+
+            let colon = line
+                .find(':')
+                .ok_or_else(|| anyhow!("Line {}: expected `<part>: <answer>`", i + 1))?;
+
+            let part = line[..colon]
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| anyhow!("Line {}: invalid part number", i + 1))?;
+
+            if parts
+                .insert(part, line[(colon + 1)..].trim().to_string())
+                .is_some()
+            {
+                bail!("Line {}: duplicate answer for part {}", i + 1, part);
+            }
+
+            last_part = Some(part);
+        }
+
+        Ok(Self { parts })
+    }
+}
+
+impl Answers {
+    /// Loads the answers at `path`, or returns `None` if there is no file.
+    pub fn load(path: &Path) -> anyhow::Result<Option<Self>> {
+        match fs::read_to_string(path) {
+            Ok(s) => Ok(Some(s.parse()?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn get(&self, part: u32) -> Option<&str> {
+        self.parts.get(&part).map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+}
+
+/// The result of comparing an answer with the expected one.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String },
+    Unknown,
+}
+
+impl Verdict {
+    pub fn check(expected: Option<&str>, answer: &Answer) -> Self {
+        match expected {
+            Some(expected) if answer.matches(expected) => Self::Pass,
+            Some(expected) => Self::Fail {
+                expected: String::from(expected),
+            },
+            None => Self::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pass => "PASS",
+            Self::Fail { .. } => "FAIL",
+            Self::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fail { expected } => write!(f, "FAIL (expected {})", expected),
+            verdict => f.write_str(verdict.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_answers() {
+        let answers = indoc! {"
+            # Handy Haversacks
+            1: 4
+
+            2:  32
+        "}
+        .parse::<Answers>()
+        .unwrap();
+
+        assert_eq!(answers.get(1), Some("4"));
+        assert_eq!(answers.get(2), Some("32"));
+        assert_eq!(answers.get(3), None);
+    }
+
+    #[test]
+    fn test_parse_invalid_answers() {
+        assert!("4".parse::<Answers>().is_err());
+        assert!("one: 4".parse::<Answers>().is_err());
+        assert!("1: 4\n1: 5".parse::<Answers>().is_err());
+        assert!("|#..#".parse::<Answers>().is_err());
+    }
+
+    #[test]
+    fn test_parse_multiline_answer() {
+        let answers = indoc! {"
+            1: 17
+            2:
+              |#..#
+              |# .#
+        "}
+        .parse::<Answers>()
+        .unwrap();
+
+        assert_eq!(answers.get(1), Some("17"));
+        assert_eq!(answers.get(2), Some("#..#\n# .#"));
+    }
+
+    #[test]
+    fn test_load_missing_answers() {
+        assert_eq!(Answers::load(Path::new("does/not/exist")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_verdict() {
+        assert_eq!(Verdict::check(Some("4"), &Answer::from(4)), Verdict::Pass);
+        assert_eq!(
+            Verdict::check(Some("4"), &Answer::from(5)),
+            Verdict::Fail {
+                expected: String::from("4")
+            }
+        );
+        assert_eq!(Verdict::check(None, &Answer::from(5)), Verdict::Unknown);
+        assert_eq!(
+            Verdict::check(Some("4"), &Answer::from(5)).to_string(),
+            "FAIL (expected 4)"
+        );
+
+        let grid = Answer::grid(vec!["#.", ".#"]);
+        assert_eq!(Verdict::check(Some("#.\n.#"), &grid), Verdict::Pass);
+    }
+}
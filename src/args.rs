@@ -0,0 +1,788 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail};
+
+use advent_of_code_2020_rs::solution::DEFAULT_STRATEGY;
+
+use crate::cache::{self, Cache};
+use crate::fetch;
+use crate::history::{self, Baseline};
+use crate::output::Format;
+
+const INPUT_DIR: &str = "input";
+
+const GENERATE_SIZE: usize = 1000;
+
+pub const USAGE: &str = "\
+Usage: advent-of-code-2020-rs [OPTIONS] [INPUT_DIR]
+       advent-of-code-2020-rs new-day <N> [NAME] [-i <DIR>]
+       advent-of-code-2020-rs generate <N> [--size <N>] [--seed <N>]
+
+Commands:
+  new-day <N> [NAME]     Create src/dayN.rs, register it and add empty input and example files
+  generate <N>           Print a random, valid input for day N with --size records [default: 1000],
+                         from --seed if given so it can be reproduced
+
+Options:
+  -d, --day <N>          Run a single day (may be repeated)
+      --days <LIST>      Run a list or range of days, e.g. 3-5 or 1,4,7
+  -a, --all              Run every day (the default)
+  -p, --part <N>         Only run part 1 or part 2
+  -i, --input-dir <DIR>  Directory containing the dayN input files [default: input]
+      --input <FILE>     Read the input for a single --day from FILE, or from stdin if FILE is -
+  -e, --example          Run on the puzzle examples in examples/dayN/ instead of the inputs
+      --lenient          Skip input records that fail to parse, listing each one, and solve with the rest
+  -s, --strategy <S>     Solve with the strategy named S, or [DAY:]S for one day only (may be repeated)
+      --cross-check      Also solve each part with every other strategy and fail if any answer differs
+      --fetch            Download missing or blank inputs into INPUT_DIR, using the AOC_SESSION token
+      --fetch-url <URL>  Like --fetch, but from another server [default: https://adventofcode.com/2020]
+  -t, --time             Report how long parsing and each part took
+      --bench <N>        Run parsing and each part N times and report min, median and p95
+  -j, --jobs <N>         Solve up to N days, and their parts, at the same time [default: 1]
+      --timeout <SECS>   Give up on parsing or a part after SECS seconds, including all --bench runs;
+                         it can't be stopped, so keeps running in the background, and --watch waits
+                         for it to finish before re-running its day
+  -w, --watch            Re-run a day whenever its input or answers file changes
+      --record           Add each part's answer and median time to the history file
+      --label <NAME>     Label the recorded run, so it can be compared with as a baseline
+      --compare          Compare each part with its latest recording in the history file
+      --baseline <NAME>  Like --compare, but with the latest run labelled NAME
+      --threshold <PCT>  Flag parts more than PCT percent slower than before [default: 10]
+      --history <FILE>   The history file [default: .aoc-history.jsonl]
+      --no-cache         Solve every part, rather than reusing answers cached for the same build and input
+      --cache-dir <DIR>  Directory to cache answers in [default: .aoc-cache]
+  -f, --format <FMT>     Output format: text, json or csv [default: text]
+  -h, --help             Print this message
+
+Each part is checked against INPUT_DIR/dayN.answers when it exists, a file
+with one `<part>: <answer>` line per part; an answer over several lines, such
+as a grid, puts each row on its own `|`-prefixed line after `<part>:`.
+Examples are checked the same way, against examples/dayN/M.answers next to
+examples/dayN/M.txt, and only run the parts that file has answers for.
+
+Defaults for --input-dir, --days, --format, --timeout, --cache-dir and
+per-day strategies can be set in aoc.toml in the current directory, and the
+flags above override them:
+
+  input-dir = \"puzzles\"
+  days = \"1-8\"
+  timeout = 2.5
+
+  [strategies]
+  1 = \"hash-set\"
+
+Answers are cached unless a run times, cross-checks, records or compares its
+parts, or parses leniently, which all need the parts to actually run.
+
+The exit status is 1 if any day fails to load, any part errors or any answer
+does not match, or, when comparing, if any part got slower than the threshold
+or its answer changed.";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// Run the solutions.
+    Run,
+    /// Scaffold a new day.
+    NewDay { day: u32, name: Option<String> },
+    /// Print a random input for a day.
+    Generate {
+        day: u32,
+        size: usize,
+        seed: Option<u64>,
+    },
+}
+
+/// A strategy choice, for one day or for any day that has it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrategyChoice {
+    pub day: Option<u32>,
+    pub name: String,
+    /// Whether the choice is a default from the config file rather than a
+    /// `--strategy` flag.
+    pub from_config: bool,
+}
+
+impl StrategyChoice {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        let (day, name) = match s.find(':') {
+            Some(i) => (Some(parse_number(&s[..i])?), &s[(i + 1)..]),
+            None => (None, s),
+        };
+
+        if name.is_empty() {
+            bail!("Missing strategy name in {}", s);
+        }
+
+        Ok(Self {
+            day,
+            name: String::from(name),
+            from_config: false,
+        })
+    }
+
+    pub fn applies_to(&self, day: u32) -> bool {
+        self.day.is_none_or(|d| d == day)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Args {
+    pub command: Command,
+    /// Days requested on the command line, or `None` to run every day.
+    pub days: Option<Vec<u32>>,
+    /// Part requested on the command line, or `None` to run both parts.
+    pub part: Option<u32>,
+    pub input_dir: PathBuf,
+    /// Input file for the single requested day, overriding `input_dir`.
+    pub input: Option<PathBuf>,
+    /// Run on the puzzle examples rather than the puzzle inputs.
+    pub example: bool,
+    /// Skip records that fail to parse rather than failing the day.
+    pub lenient: bool,
+    /// Strategies to solve with instead of the default, in order of
+    /// preference.
+    pub strategies: Vec<StrategyChoice>,
+    /// Solve each part with all of its strategies and compare the answers.
+    pub cross_check: bool,
+    /// Where to download missing inputs from, if anywhere.
+    pub fetch_url: Option<String>,
+    /// Session token sent when downloading inputs. Read from the environment
+    /// rather than the command line.
+    pub session: Option<String>,
+    pub time: bool,
+    /// Number of times to repeat each part when benchmarking.
+    pub bench: Option<usize>,
+    /// Number of days to run at the same time.
+    pub jobs: usize,
+    /// Wall-clock limit for parsing and for each part.
+    pub timeout: Option<Duration>,
+    pub format: Format,
+    pub watch: bool,
+    /// Add the run to the history file.
+    pub record: bool,
+    /// Label for the recorded run.
+    pub label: Option<String>,
+    /// What to compare the run with, if anything.
+    pub compare: Option<Baseline>,
+    /// Percentage a part may slow down by before the comparison flags it.
+    pub threshold: f64,
+    pub history: PathBuf,
+    /// Solve every part rather than reusing cached answers.
+    pub no_cache: bool,
+    pub cache_dir: PathBuf,
+    /// Identifies the running build, for the answer cache. Worked out by
+    /// `main` rather than given on the command line.
+    pub build: Option<u64>,
+    pub help: bool,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            command: Command::Run,
+            days: None,
+            part: None,
+            input_dir: PathBuf::from(INPUT_DIR),
+            input: None,
+            example: false,
+            lenient: false,
+            strategies: Vec::new(),
+            cross_check: false,
+            fetch_url: None,
+            session: None,
+            time: false,
+            bench: None,
+            jobs: 1,
+            timeout: None,
+            format: Format::Text,
+            watch: false,
+            record: false,
+            label: None,
+            compare: None,
+            threshold: history::THRESHOLD,
+            history: PathBuf::from(history::HISTORY_FILE),
+            no_cache: false,
+            cache_dir: PathBuf::from(cache::CACHE_DIR),
+            build: None,
+            help: false,
+        }
+    }
+}
+
+pub fn parse_number(s: &str) -> anyhow::Result<u32> {
+    let n = s
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| anyhow!("Invalid number: {}", s))?;
+
+    Ok(n)
+}
+
+pub fn parse_duration(s: &str) -> anyhow::Result<Duration> {
+    let seconds = s
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| anyhow!("Invalid number of seconds: {}", s))?;
+
+    if !seconds.is_finite() || seconds <= 0.0 {
+        bail!("Timeout must be a positive number of seconds, got {}", s);
+    }
+
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+fn parse_threshold(s: &str) -> anyhow::Result<f64> {
+    let percent = s
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| anyhow!("Invalid percentage: {}", s))?;
+
+    if !percent.is_finite() || percent < 0.0 {
+        bail!("Threshold must be a percentage of at least 0, got {}", s);
+    }
+
+    Ok(percent)
+}
+
+pub fn parse_day_list(s: &str) -> anyhow::Result<Vec<u32>> {
+    let mut days = Vec::new();
+
+    for item in s.split(',') {
+        match item.find('-') {
+            Some(i) => {
+                let start = parse_number(&item[..i])?;
+                let end = parse_number(&item[(i + 1)..])?;
+
+                if start > end {
+                    bail!("Invalid day range: {}", item);
+                }
+
+                days.extend(start..=end);
+            }
+            None => days.push(parse_number(item)?),
+        }
+    }
+
+    Ok(days)
+}
+
+impl Args {
+    /// Parses `args`, taking anything they leave out from `defaults`, such
+    /// as a config file's settings.
+    pub fn parse<I>(defaults: Self, args: I) -> anyhow::Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut parsed = defaults;
+        let mut days = Vec::new();
+        let mut strategies = Vec::new();
+        let mut all = false;
+        let mut positional = None;
+
+        let mut args = args.into_iter().peekable();
+
+        match args.peek().map(String::as_str) {
+            Some("new-day") => {
+                args.next();
+
+                let day = args
+                    .next()
+                    .ok_or_else(|| anyhow!("Missing day for new-day"))?;
+
+                parsed.command = Command::NewDay {
+                    day: parse_number(&day)?,
+                    name: None,
+                };
+            }
+            Some("generate") => {
+                args.next();
+
+                let day = args
+                    .next()
+                    .ok_or_else(|| anyhow!("Missing day for generate"))?;
+
+                parsed.command = Command::Generate {
+                    day: parse_number(&day)?,
+                    size: GENERATE_SIZE,
+                    seed: None,
+                };
+            }
+            _ => {}
+        }
+
+        while let Some(arg) = args.next() {
+            // Support both `--flag value` and `--flag=value`.
+            let (flag, inline_value) = match arg.find('=') {
+                Some(i) if arg.starts_with("--") => {
+                    (arg[..i].to_string(), Some(arg[(i + 1)..].to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| anyhow!("Missing value for {}", flag))
+            };
+
+            match flag.as_str() {
+                "-d" | "--day" => days.push(parse_number(&value()?)?),
+                "--days" => days.extend(parse_day_list(&value()?)?),
+                "-a" | "--all" => all = true,
+                "-p" | "--part" => {
+                    let part = parse_number(&value()?)?;
+
+                    if part != 1 && part != 2 {
+                        bail!("Part must be 1 or 2, got {}", part);
+                    }
+
+                    parsed.part = Some(part);
+                }
+                "-i" | "--input-dir" => parsed.input_dir = value()?.into(),
+                "--input" => parsed.input = Some(value()?.into()),
+                "-e" | "--example" => parsed.example = true,
+                "--lenient" => parsed.lenient = true,
+                "-s" | "--strategy" => strategies.push(StrategyChoice::parse(&value()?)?),
+                "--cross-check" => parsed.cross_check = true,
+                "--fetch" => {
+                    parsed
+                        .fetch_url
+                        .get_or_insert_with(|| fetch::DEFAULT_URL.to_string());
+                }
+                "--fetch-url" => parsed.fetch_url = Some(value()?),
+                "-t" | "--time" => parsed.time = true,
+                "--bench" => {
+                    let runs = parse_number(&value()?)?;
+
+                    if runs == 0 {
+                        bail!("--bench needs at least 1 run");
+                    }
+
+                    parsed.bench = Some(runs as usize);
+                }
+                "-j" | "--jobs" => {
+                    let jobs = parse_number(&value()?)?;
+
+                    if jobs == 0 {
+                        bail!("--jobs needs at least 1 thread");
+                    }
+
+                    parsed.jobs = jobs as usize;
+                }
+                "--timeout" => parsed.timeout = Some(parse_duration(&value()?)?),
+                "-f" | "--format" => parsed.format = value()?.parse()?,
+                "-w" | "--watch" => parsed.watch = true,
+                "--record" => parsed.record = true,
+                "--label" => parsed.label = Some(value()?),
+                "--compare" => {
+                    parsed.compare.get_or_insert(Baseline::Previous);
+                }
+                "--baseline" => parsed.compare = Some(Baseline::Label(value()?)),
+                "--threshold" => parsed.threshold = parse_threshold(&value()?)?,
+                "--history" => parsed.history = value()?.into(),
+                "--no-cache" => parsed.no_cache = true,
+                "--cache-dir" => parsed.cache_dir = value()?.into(),
+                "--size" | "--seed" => {
+                    let n = value()?;
+                    let n = n
+                        .trim()
+                        .parse::<u64>()
+                        .map_err(|_| anyhow!("Invalid number: {}", n))?;
+
+                    match (&mut parsed.command, flag.as_str()) {
+                        (Command::Generate { size, .. }, "--size") => *size = n as usize,
+                        (Command::Generate { seed, .. }, _) => *seed = Some(n),
+                        _ => bail!("{} only applies to generate", flag),
+                    }
+                }
+                "-h" | "--help" => parsed.help = true,
+                _ if arg.starts_with('-') => bail!("Unrecognized option: {}", arg),
+                _ => {
+                    if positional.replace(arg).is_some() {
+                        bail!("Unexpected extra argument");
+                    }
+                }
+            }
+        }
+
+        if all && !days.is_empty() {
+            bail!("--all cannot be combined with --day or --days");
+        }
+
+        if parsed.input.is_some() && days.len() != 1 {
+            bail!("--input needs exactly one --day");
+        }
+
+        if parsed.example && parsed.input.is_some() {
+            bail!("--example cannot be combined with --input");
+        }
+
+        if parsed.watch && parsed.input.as_deref() == Some(Path::new("-")) {
+            bail!("--watch cannot read input from stdin");
+        }
+
+        if parsed.watch && (parsed.record || parsed.compare.is_some()) {
+            bail!("--watch cannot be combined with --record or --compare");
+        }
+
+        if parsed.label.is_some() && !parsed.record {
+            bail!("--label needs --record");
+        }
+
+        match (&mut parsed.command, positional) {
+            (Command::NewDay { name, .. }, positional) => *name = positional,
+            (Command::Generate { .. }, Some(_)) => bail!("Unexpected extra argument"),
+            (Command::Generate { .. }, None) => {}
+            (Command::Run, Some(dir)) => parsed.input_dir = dir.into(),
+            (Command::Run, None) => {}
+        }
+
+        if !days.is_empty() {
+            days.sort_unstable();
+            days.dedup();
+            parsed.days = Some(days);
+        } else if all {
+            parsed.days = None;
+        }
+
+        // Strategies given on the command line come first, so they are
+        // preferred over the defaults.
+        strategies.append(&mut parsed.strategies);
+        parsed.strategies = strategies;
+
+        Ok(parsed)
+    }
+
+    pub fn runs_day(&self, day: u32) -> bool {
+        self.days.as_ref().is_none_or(|days| days.contains(&day))
+    }
+
+    pub fn runs_part(&self, part: u32) -> bool {
+        self.part.is_none_or(|p| p == part)
+    }
+
+    /// The strategy to solve a part of `day` with, out of the `available`
+    /// ones: the first `--strategy` for that day it has, or the default.
+    pub fn strategy(&self, day: u32, available: &[&'static str]) -> &'static str {
+        self.strategies
+            .iter()
+            .filter(|choice| choice.applies_to(day))
+            .find_map(|choice| available.iter().find(|name| **name == choice.name))
+            .copied()
+            .unwrap_or(DEFAULT_STRATEGY)
+    }
+
+    /// Whether answers are cached. Parts that are being timed,
+    /// cross-checked, recorded or compared, or parsed leniently so that
+    /// skipped records are listed, always run.
+    pub fn uses_cache(&self) -> bool {
+        !(self.no_cache
+            || self.shows_time()
+            || self.cross_check
+            || self.record
+            || self.compare.is_some()
+            || self.lenient)
+    }
+
+    /// The answer cache to use, if any.
+    pub fn cache(&self) -> Option<Cache> {
+        match self.build {
+            Some(build) if self.uses_cache() => Some(Cache::new(&self.cache_dir, build)),
+            _ => None,
+        }
+    }
+
+    /// Where to read the input for `day` from. A path of `-` means stdin.
+    pub fn input_path(&self, day: u32) -> PathBuf {
+        match &self.input {
+            Some(path) => path.clone(),
+            None => self.input_dir.join(format!("day{}", day)),
+        }
+    }
+
+    /// How many times to run parsing and each part.
+    pub fn runs(&self) -> usize {
+        self.bench.unwrap_or(1)
+    }
+
+    pub fn shows_time(&self) -> bool {
+        self.time || self.bench.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Args> {
+        Args::parse(Args::default(), args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        assert_eq!(parse(&[]).unwrap(), Args::default());
+        assert_eq!(parse(&["--all"]).unwrap(), Args::default());
+    }
+
+    #[test]
+    fn test_parse_day_and_part() {
+        let args = parse(&["--day", "7", "--part", "2"]).unwrap();
+
+        assert_eq!(args.days, Some(vec![7]));
+        assert_eq!(args.part, Some(2));
+        assert!(args.runs_day(7));
+        assert!(!args.runs_day(6));
+        assert!(args.runs_part(2));
+        assert!(!args.runs_part(1));
+    }
+
+    #[test]
+    fn test_parse_day_list() {
+        assert_eq!(parse_day_list("3-5").unwrap(), vec![3, 4, 5]);
+        assert_eq!(parse_day_list("1,4-5,8").unwrap(), vec![1, 4, 5, 8]);
+        assert!(parse_day_list("5-3").is_err());
+        assert!(parse_day_list("x").is_err());
+
+        let args = parse(&["--days=3-5", "-d", "4", "-d", "1"]).unwrap();
+
+        assert_eq!(args.days, Some(vec![1, 3, 4, 5]));
+    }
+
+    #[test]
+    fn test_parse_input_dir() {
+        let args = parse(&["--input-dir", "other"]).unwrap();
+        assert_eq!(args.input_dir, PathBuf::from("other"));
+
+        let args = parse(&["positional"]).unwrap();
+        assert_eq!(args.input_dir, PathBuf::from("positional"));
+    }
+
+    #[test]
+    fn test_parse_input() {
+        let args = parse(&["--day", "8", "--input", "-"]).unwrap();
+        assert_eq!(args.input_path(8), Path::new("-"));
+
+        let args = parse(&["--input=sample", "--day", "8"]).unwrap();
+        assert_eq!(args.input_path(8), Path::new("sample"));
+
+        let args = parse(&["other"]).unwrap();
+        assert_eq!(args.input_path(8), Path::new("other/day8"));
+
+        assert!(parse(&["--input", "sample"]).is_err());
+        assert!(parse(&["--days", "1-2", "--input", "sample"]).is_err());
+        assert!(parse(&["--day", "8", "--input", "-", "--watch"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_example() {
+        assert!(parse(&["--example"]).unwrap().example);
+        assert!(!parse(&[]).unwrap().example);
+        assert!(parse(&["-e", "--day", "8", "--input", "sample"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        assert!(parse(&["--lenient"]).unwrap().lenient);
+        assert!(!parse(&[]).unwrap().lenient);
+    }
+
+    #[test]
+    fn test_parse_strategy() {
+        let args = parse(&[
+            "-s",
+            "hash-set",
+            "--strategy=7:reverse-index",
+            "--cross-check",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            args.strategies,
+            vec![
+                StrategyChoice {
+                    day: None,
+                    name: String::from("hash-set"),
+                    from_config: false,
+                },
+                StrategyChoice {
+                    day: Some(7),
+                    name: String::from("reverse-index"),
+                    from_config: false,
+                },
+            ]
+        );
+        assert!(args.cross_check);
+
+        assert_eq!(args.strategy(1, &["default", "hash-set"]), "hash-set");
+        assert_eq!(
+            args.strategy(7, &["default", "reverse-index"]),
+            "reverse-index"
+        );
+        assert_eq!(args.strategy(3, &["default", "reverse-index"]), "default");
+
+        assert!(parse(&["--strategy", "7:"]).is_err());
+        assert!(parse(&["--strategy", "x:fast"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_fetch() {
+        assert_eq!(parse(&[]).unwrap().fetch_url, None);
+        assert_eq!(
+            parse(&["--fetch"]).unwrap().fetch_url.as_deref(),
+            Some(fetch::DEFAULT_URL)
+        );
+        assert_eq!(
+            parse(&["--fetch-url", "http://localhost:8000"])
+                .unwrap()
+                .fetch_url
+                .as_deref(),
+            Some("http://localhost:8000")
+        );
+    }
+
+    #[test]
+    fn test_parse_timing() {
+        let args = parse(&["--time"]).unwrap();
+        assert!(args.shows_time());
+        assert_eq!(args.runs(), 1);
+
+        let args = parse(&["--bench", "50"]).unwrap();
+        assert!(args.shows_time());
+        assert_eq!(args.runs(), 50);
+
+        assert!(!parse(&[]).unwrap().shows_time());
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        let args = parse(&["--timeout", "1.5"]).unwrap();
+        assert_eq!(args.timeout, Some(Duration::from_millis(1500)));
+
+        assert!(parse(&["--timeout", "0"]).is_err());
+        assert!(parse(&["--timeout", "-1"]).is_err());
+        assert!(parse(&["--timeout", "soon"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_jobs() {
+        assert_eq!(parse(&[]).unwrap().jobs, 1);
+        assert_eq!(parse(&["-j", "4"]).unwrap().jobs, 4);
+        assert!(parse(&["--jobs", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(parse(&["--format", "csv"]).unwrap().format, Format::Csv);
+        assert_eq!(parse(&["-f", "json"]).unwrap().format, Format::Json);
+        assert!(parse(&["--format", "yaml"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_history() {
+        let args = parse(&["--record", "--label", "before", "--compare"]).unwrap();
+
+        assert!(args.record);
+        assert_eq!(args.label.as_deref(), Some("before"));
+        assert_eq!(args.compare, Some(Baseline::Previous));
+        assert_eq!(args.history, PathBuf::from(history::HISTORY_FILE));
+
+        let args = parse(&[
+            "--baseline=before",
+            "--threshold",
+            "25",
+            "--history",
+            "h.jsonl",
+        ])
+        .unwrap();
+
+        assert_eq!(args.compare, Some(Baseline::Label(String::from("before"))));
+        assert_eq!(args.threshold, 25.0);
+        assert_eq!(args.history, PathBuf::from("h.jsonl"));
+
+        assert!(parse(&["--label", "before"]).is_err());
+        assert!(parse(&["--compare", "--watch"]).is_err());
+        assert!(parse(&["--threshold", "-5"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_cache() {
+        let mut args = parse(&["--cache-dir", "cached"]).unwrap();
+        assert!(args.uses_cache());
+        assert_eq!(args.cache(), None);
+
+        args.build = Some(7);
+        assert_eq!(args.cache(), Some(Cache::new(Path::new("cached"), 7)));
+
+        for flags in &[
+            &["--no-cache"][..],
+            &["--time"],
+            &["--cross-check"],
+            &["--record"],
+            &["--compare"],
+            &["--lenient"],
+            &["--bench", "5"],
+        ] {
+            let mut args = parse(flags).unwrap();
+            args.build = Some(7);
+
+            assert!(!args.uses_cache(), "{:?}", flags);
+            assert_eq!(args.cache(), None, "{:?}", flags);
+        }
+    }
+
+    #[test]
+    fn test_parse_new_day() {
+        let args = parse(&["new-day", "9", "Encoding Error", "-i", "other"]).unwrap();
+
+        assert_eq!(
+            args.command,
+            Command::NewDay {
+                day: 9,
+                name: Some(String::from("Encoding Error"))
+            }
+        );
+        assert_eq!(args.input_dir, PathBuf::from("other"));
+
+        assert!(parse(&["new-day"]).is_err());
+        assert!(parse(&["new-day", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_generate() {
+        let args = parse(&["generate", "7", "--size", "50000", "--seed=42"]).unwrap();
+
+        assert_eq!(
+            args.command,
+            Command::Generate {
+                day: 7,
+                size: 50000,
+                seed: Some(42)
+            }
+        );
+
+        assert_eq!(
+            parse(&["generate", "1"]).unwrap().command,
+            Command::Generate {
+                day: 1,
+                size: GENERATE_SIZE,
+                seed: None
+            }
+        );
+
+        assert!(parse(&["generate"]).is_err());
+        assert!(parse(&["generate", "1", "extra"]).is_err());
+        assert!(parse(&["--seed", "42"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(&["--part", "3"]).is_err());
+        assert!(parse(&["--bench", "0"]).is_err());
+        assert!(parse(&["--day"]).is_err());
+        assert!(parse(&["--all", "--day", "1"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+        assert!(parse(&["a", "b"]).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Summary of the wall-clock time taken by one or more runs of a closure.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+}
+
+/// Picks the nearest-rank percentile out of already sorted samples.
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    let rank = (sorted.len() * percent).div_ceil(100);
+
+    sorted[rank.saturating_sub(1)]
+}
+
+impl Stats {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            samples.push(Duration::default());
+        }
+
+        samples.sort_unstable();
+
+        Self {
+            runs: samples.len(),
+            min: samples[0],
+            median: percentile(&samples, 50),
+            p95: percentile(&samples, 95),
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.runs == 1 {
+            write!(f, "{:.2?}", self.min)
+        } else {
+            write!(
+                f,
+                "min {:.2?}, median {:.2?}, p95 {:.2?} ({} runs)",
+                self.min, self.median, self.p95, self.runs
+            )
+        }
+    }
+}
+
+/// Calls `f` up to `runs` times, timing each call.
+///
+/// Stops early on the first error, since solvers are deterministic and the
+/// remaining runs would fail the same way. Returns the result of the last
+/// call made.
+pub fn run<T, F>(runs: usize, mut f: F) -> (anyhow::Result<T>, Stats)
+where
+    F: FnMut() -> anyhow::Result<T>,
+{
+    let mut samples = Vec::with_capacity(runs);
+
+    loop {
+        let start = Instant::now();
+        let result = f();
+        samples.push(start.elapsed());
+
+        if result.is_err() || samples.len() >= runs {
+            return (result, Stats::from_samples(samples));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().copied().map(Duration::from_millis).collect()
+    }
+
+    #[test]
+    fn test_stats_from_samples() {
+        let stats = Stats::from_samples(millis(&[9, 1, 5, 3, 7]));
+
+        assert_eq!(stats.runs, 5);
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_millis(5));
+        assert_eq!(stats.p95, Duration::from_millis(9));
+
+        let stats = Stats::from_samples(millis(&(1..=100).collect::<Vec<_>>()));
+
+        assert_eq!(stats.median, Duration::from_millis(50));
+        assert_eq!(stats.p95, Duration::from_millis(95));
+    }
+
+    #[test]
+    fn test_run_repeats() {
+        let mut calls = 0;
+        let (result, stats) = run(10, || {
+            calls += 1;
+            Ok(calls)
+        });
+
+        assert_eq!(result.unwrap(), 10);
+        assert_eq!(stats.runs, 10);
+    }
+
+    #[test]
+    fn test_run_stops_on_error() {
+        let mut calls = 0;
+        let (result, stats) = run(10, || -> anyhow::Result<()> {
+            calls += 1;
+            anyhow::bail!("failed")
+        });
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+        assert_eq!(stats.runs, 1);
+    }
+}
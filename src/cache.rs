@@ -0,0 +1,204 @@
+//! A cache of answers, so that re-running unchanged code on an unchanged
+//! input skips straight to the answer.
+//!
+//! Each answer lives in its own file under the cache directory, keyed by the
+//! day, the part, the strategy, a hash of the input and an id for the build
+//! that produced it. Any change to the code makes a new build id, so stale
+//! answers are never read back.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use advent_of_code_2020_rs::answer::Answer;
+use anyhow::anyhow;
+
+use crate::json::Value;
+
+pub const CACHE_DIR: &str = ".aoc-cache";
+
+/// Hashes `bytes` with 64-bit FNV-1a.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Identifies the running build by a hash of its executable, or `None` if
+/// that can't be read.
+pub fn build_id() -> Option<u64> {
+    let exe = env::current_exe().ok()?;
+
+    fs::read(exe).ok().map(|bytes| fnv1a(&bytes))
+}
+
+/// What an answer was computed from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Key<'a> {
+    pub day: u32,
+    pub part: u32,
+    pub strategy: &'a str,
+    /// The FNV-1a hash of the input.
+    pub input: u64,
+}
+
+impl Key<'_> {
+    fn to_json(self, build: u64) -> Value {
+        Value::Object(vec![
+            (String::from("day"), self.day.into()),
+            (String::from("part"), self.part.into()),
+            (String::from("strategy"), self.strategy.into()),
+            (String::from("input"), format!("{:016x}", self.input).into()),
+            (String::from("build"), format!("{:016x}", build).into()),
+        ])
+    }
+
+    fn file_name(self, build: u64) -> String {
+        let hash = fnv1a(self.to_json(build).to_string().as_bytes());
+
+        format!("day{}-part{}-{:016x}", self.day, self.part, hash)
+    }
+}
+
+/// Answers keep their shape in the cache, since a list and a grid would
+/// otherwise both read back as an array.
+fn answer_to_json(answer: &Answer) -> Value {
+    let (kind, value) = match answer {
+        Answer::Integer(n) => ("integer", Value::Integer(*n)),
+        Answer::String(s) => ("string", Value::from(s.as_str())),
+        Answer::List(items) => (
+            "list",
+            Value::Array(items.iter().map(answer_to_json).collect()),
+        ),
+        Answer::Grid(_) => ("grid", Value::from(answer)),
+    };
+
+    Value::Object(vec![(String::from(kind), value)])
+}
+
+fn answer_from_json(value: &Value) -> anyhow::Result<Answer> {
+    let invalid = || anyhow!("Invalid cached answer: {}", value);
+
+    let (kind, value) = match value {
+        Value::Object(fields) if fields.len() == 1 => &fields[0],
+        _ => return Err(invalid()),
+    };
+
+    let answer = match (kind.as_str(), value) {
+        ("integer", Value::Integer(n)) => Answer::Integer(*n),
+        ("string", Value::String(s)) => Answer::String(s.clone()),
+        ("list", Value::Array(items)) => Answer::List(
+            items
+                .iter()
+                .map(answer_from_json)
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        ("grid", Value::Array(rows)) => Answer::grid(
+            rows.iter()
+                .map(|row| row.as_str().ok_or_else(invalid))
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        ),
+        _ => return Err(invalid()),
+    };
+
+    Ok(answer)
+}
+
+/// The answers of one build, cached in a directory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cache {
+    dir: PathBuf,
+    build: u64,
+}
+
+impl Cache {
+    pub fn new(dir: &Path, build: u64) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            build,
+        }
+    }
+
+    fn path(&self, key: Key) -> PathBuf {
+        self.dir.join(key.file_name(self.build))
+    }
+
+    /// The answer cached for `key`, if there is one. A missing or unreadable
+    /// entry is a miss.
+    pub fn get(&self, key: Key) -> Option<Answer> {
+        let text = fs::read_to_string(self.path(key)).ok()?;
+        let value: Value = text.parse().ok()?;
+
+        // The file name is only a hash, so check the entry is really for
+        // this key.
+        if value.get("key") != Some(&key.to_json(self.build)) {
+            return None;
+        }
+
+        answer_from_json(value.get("answer")?).ok()
+    }
+
+    /// Caches `answer` for `key`.
+    pub fn put(&self, key: Key, answer: &Answer) -> anyhow::Result<()> {
+        let entry = Value::Object(vec![
+            (String::from("key"), key.to_json(self.build)),
+            (String::from("answer"), answer_to_json(answer)),
+        ]);
+
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(key), entry.to_string())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn test_cache() {
+        let dir = env::temp_dir().join(format!("aoc-cache-test-{}", std::process::id()));
+        let cache = Cache::new(&dir, 7);
+
+        let key = Key {
+            day: 1,
+            part: 2,
+            strategy: "default",
+            input: fnv1a(b"1721\n979\n"),
+        };
+
+        assert_eq!(cache.get(key), None);
+
+        let answers = vec![
+            Answer::from(-241_861_950_i64),
+            Answer::from("abc"),
+            Answer::from(vec![3, 4]),
+            Answer::grid(vec!["#.", ".#"]),
+        ];
+
+        for answer in &answers {
+            cache.put(key, answer).unwrap();
+            assert_eq!(cache.get(key).as_ref(), Some(answer));
+        }
+
+        assert_eq!(Cache::new(&dir, 8).get(key), None);
+        assert_eq!(cache.get(Key { input: 0, ..key }), None);
+        assert_eq!(
+            cache.get(Key {
+                strategy: "hash-set",
+                ..key
+            }),
+            None
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,531 @@
+//! Runner defaults read from `aoc.toml`, which command-line flags override.
+//!
+//! Only the part of TOML the file needs is understood: comments, `[table]`
+//! headers and `key = value` lines whose values are strings, integers,
+//! floats, booleans or single-line arrays of them.
+//!
+//! ```toml
+//! input-dir = "puzzles"
+//! days = "1-8"
+//! format = "text"
+//! timeout = 2.5
+//! cache-dir = ".aoc-cache"
+//!
+//! [strategies]
+//! 1 = "hash-set"
+//! 7 = ["reverse-index"]
+//! ```
+
+use std::fs;
+use std::io;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context as _};
+
+use crate::args::{self, Args, StrategyChoice};
+use crate::output::Format;
+
+pub const CONFIG_FILE: &str = "aoc.toml";
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::String(_) => "a string",
+            Self::Integer(_) => "an integer",
+            Self::Float(_) => "a float",
+            Self::Bool(_) => "a boolean",
+            Self::Array(_) => "an array",
+        }
+    }
+}
+
+/// A `key = value` line, with the table it is in.
+#[derive(Debug, PartialEq)]
+struct Entry {
+    line: usize,
+    table: Option<String>,
+    key: String,
+    value: Value,
+}
+
+/// Reads a value or key from the rest of a line.
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Self {
+            chars: s.chars().peekable(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t') = self.chars.peek() {
+            self.chars.next();
+        }
+    }
+
+    /// Checks nothing but whitespace and a comment is left.
+    fn end(&mut self) -> anyhow::Result<()> {
+        self.skip_whitespace();
+
+        match self.chars.next() {
+            None | Some('#') => Ok(()),
+            Some(c) => bail!("Unexpected '{}'", c),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> anyhow::Result<()> {
+        self.skip_whitespace();
+
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => bail!("Expected '{}', found '{}'", expected, c),
+            None => bail!("Expected '{}'", expected),
+        }
+    }
+
+    fn bare(&mut self) -> String {
+        let mut s = String::new();
+
+        while let Some(&c) = self.chars.peek() {
+            if !(c.is_ascii_alphanumeric() || "_-+.".contains(c)) {
+                break;
+            }
+
+            s.push(c);
+            self.chars.next();
+        }
+
+        s
+    }
+
+    fn key(&mut self) -> anyhow::Result<String> {
+        self.skip_whitespace();
+
+        let key = match self.chars.peek() {
+            Some('"' | '\'') => self.string()?,
+            _ => self.bare(),
+        };
+
+        if key.is_empty() {
+            bail!("Missing key");
+        }
+
+        Ok(key)
+    }
+
+    fn string(&mut self) -> anyhow::Result<String> {
+        let quote = self.chars.next().ok_or_else(|| anyhow!("Missing string"))?;
+        let mut s = String::new();
+
+        loop {
+            match self.chars.next() {
+                None => bail!("Unterminated string"),
+                Some(c) if c == quote => return Ok(s),
+                // Single-quoted strings are literal.
+                Some('\\') if quote == '"' => match self.chars.next() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some(c) => bail!("Invalid escape: \\{}", c),
+                    None => bail!("Unterminated string"),
+                },
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn value(&mut self) -> anyhow::Result<Value> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some('"' | '\'') => Ok(Value::String(self.string()?)),
+            Some('[') => {
+                self.chars.next();
+
+                let mut values = Vec::new();
+
+                loop {
+                    self.skip_whitespace();
+
+                    if let Some(']') = self.chars.peek() {
+                        self.chars.next();
+                        return Ok(Value::Array(values));
+                    }
+
+                    values.push(self.value()?);
+                    self.skip_whitespace();
+
+                    match self.chars.next() {
+                        Some(',') => {}
+                        Some(']') => return Ok(Value::Array(values)),
+                        Some(c) => bail!("Expected ',' or ']', found '{}'", c),
+                        None => bail!("Unterminated array"),
+                    }
+                }
+            }
+            Some(_) => {
+                let word = self.bare();
+
+                match word.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    "" => bail!("Missing value"),
+                    _ => {
+                        let number = word.replace('_', "");
+
+                        if let Ok(n) = number.parse() {
+                            Ok(Value::Integer(n))
+                        } else if let Ok(n) = number.parse() {
+                            Ok(Value::Float(n))
+                        } else {
+                            bail!("Invalid value: {}", word)
+                        }
+                    }
+                }
+            }
+            None => bail!("Missing value"),
+        }
+    }
+}
+
+fn parse_line(line: &str, table: &mut Option<String>) -> anyhow::Result<Option<(String, Value)>> {
+    let mut parser = Parser::new(line);
+    parser.skip_whitespace();
+
+    match parser.chars.peek() {
+        None | Some('#') => Ok(None),
+        Some('[') => {
+            parser.chars.next();
+            *table = Some(parser.key()?);
+            parser.expect(']')?;
+            parser.end()?;
+
+            Ok(None)
+        }
+        Some(_) => {
+            let key = parser.key()?;
+            parser.expect('=')?;
+            let value = parser.value()?;
+            parser.end()?;
+
+            Ok(Some((key, value)))
+        }
+    }
+}
+
+/// Splits TOML text into its entries, in order.
+fn parse_toml(s: &str) -> anyhow::Result<Vec<Entry>> {
+    let mut table = None;
+    let mut entries: Vec<Entry> = Vec::new();
+
+    for (i, line) in s.lines().enumerate() {
+        let line_number = i + 1;
+
+        let parsed =
+            parse_line(line, &mut table).with_context(|| format!("Line {}", line_number))?;
+
+        if let Some((key, value)) = parsed {
+            let duplicate = entries
+                .iter()
+                .any(|entry| entry.table == table && entry.key == key);
+
+            if duplicate {
+                bail!("Line {}: {} is set twice", line_number, key);
+            }
+
+            entries.push(Entry {
+                line: line_number,
+                table: table.clone(),
+                key,
+                value,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Settings from a config file. Anything left out keeps its usual default.
+#[derive(Debug, Default, PartialEq)]
+pub struct Config {
+    pub input_dir: Option<PathBuf>,
+    pub days: Option<Vec<u32>>,
+    pub format: Option<Format>,
+    pub timeout: Option<Duration>,
+    /// Strategies for particular days, in order of preference.
+    pub strategies: Vec<StrategyChoice>,
+    pub cache_dir: Option<PathBuf>,
+}
+
+fn string(value: &Value) -> anyhow::Result<&str> {
+    match value {
+        Value::String(s) => Ok(s),
+        value => bail!("Expected a string, found {}", value.type_name()),
+    }
+}
+
+fn days(value: &Value) -> anyhow::Result<Vec<u32>> {
+    match value {
+        Value::String(s) => args::parse_day_list(s),
+        Value::Integer(n) => args::parse_number(&n.to_string()).map(|day| vec![day]),
+        Value::Array(values) => values
+            .iter()
+            .map(|value| match value {
+                Value::Integer(n) => args::parse_number(&n.to_string()),
+                value => bail!("Expected a day, found {}", value.type_name()),
+            })
+            .collect(),
+        value => bail!("Expected days, found {}", value.type_name()),
+    }
+}
+
+fn timeout(value: &Value) -> anyhow::Result<Duration> {
+    match value {
+        Value::Integer(n) => args::parse_duration(&n.to_string()),
+        Value::Float(n) => args::parse_duration(&n.to_string()),
+        value => bail!("Expected seconds, found {}", value.type_name()),
+    }
+}
+
+fn strategies(day: &str, value: &Value) -> anyhow::Result<Vec<StrategyChoice>> {
+    let day = args::parse_number(day)?;
+
+    let names = match value {
+        Value::Array(values) => values.iter().map(string).collect::<anyhow::Result<_>>()?,
+        value => vec![string(value)?],
+    };
+
+    Ok(names
+        .into_iter()
+        .map(|name| StrategyChoice {
+            day: Some(day),
+            name: String::from(name),
+            from_config: true,
+        })
+        .collect())
+}
+
+impl FromStr for Config {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Self::default();
+
+        for entry in parse_toml(s)? {
+            let value = &entry.value;
+
+            let set = match (entry.table.as_deref(), entry.key.as_str()) {
+                (None, "input-dir") => string(value).map(|dir| config.input_dir = Some(dir.into())),
+                (None, "days") => days(value).map(|days| config.days = Some(days)),
+                (None, "format") => string(value)
+                    .and_then(str::parse)
+                    .map(|format| config.format = Some(format)),
+                (None, "timeout") => timeout(value).map(|timeout| config.timeout = Some(timeout)),
+                (None, "cache-dir") => string(value).map(|dir| config.cache_dir = Some(dir.into())),
+                (Some("strategies"), day) => {
+                    strategies(day, value).map(|choices| config.strategies.extend(choices))
+                }
+                (Some(table), key) => Err(anyhow!("Unknown setting {}.{}", table, key)),
+                (None, key) => Err(anyhow!("Unknown setting {}", key)),
+            };
+
+            set.with_context(|| format!("Line {}", entry.line))?;
+        }
+
+        Ok(config)
+    }
+}
+
+impl Config {
+    /// Reads the config at `path`. A missing file sets nothing.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => text.parse().with_context(|| path.display().to_string()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("Reading {}", path.display())),
+        }
+    }
+
+    /// The arguments to use when the command line leaves them out.
+    pub fn defaults(&self) -> Args {
+        let defaults = Args::default();
+
+        Args {
+            input_dir: self.input_dir.clone().unwrap_or(defaults.input_dir),
+            days: self.days.clone(),
+            format: self.format.unwrap_or(defaults.format),
+            timeout: self.timeout,
+            strategies: self.strategies.clone(),
+            cache_dir: self.cache_dir.clone().unwrap_or(defaults.cache_dir),
+            ..defaults
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_toml() {
+        let entries = parse_toml(indoc! {r#"
+            # A comment
+            name = "a # b" # trailing
+            list = [1, 2.5, 'c:\d', true,]
+
+            [table]
+            "quoted key" = -3_000
+        "#})
+        .unwrap();
+
+        let entries: Vec<_> = entries
+            .into_iter()
+            .map(|entry| (entry.table, entry.key, entry.value))
+            .collect();
+
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    None,
+                    String::from("name"),
+                    Value::String(String::from("a # b"))
+                ),
+                (
+                    None,
+                    String::from("list"),
+                    Value::Array(vec![
+                        Value::Integer(1),
+                        Value::Float(2.5),
+                        Value::String(String::from("c:\\d")),
+                        Value::Bool(true),
+                    ])
+                ),
+                (
+                    Some(String::from("table")),
+                    String::from("quoted key"),
+                    Value::Integer(-3000)
+                ),
+            ]
+        );
+
+        for text in &[
+            "x",
+            "x = ",
+            "x = [1",
+            "x = \"a",
+            "x = 1 2",
+            "[t",
+            "x = 1\nx = 2",
+            "x = nope",
+        ] {
+            assert!(parse_toml(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config: Config = indoc! {r#"
+            input-dir = "puzzles"
+            days = "1-3"
+            format = "csv"
+            timeout = 2.5
+            cache-dir = "/tmp/aoc"
+
+            [strategies]
+            1 = "hash-set"
+            7 = ["reverse-index", "default"]
+        "#}
+        .parse()
+        .unwrap();
+
+        let choice = |day, name: &str| StrategyChoice {
+            day: Some(day),
+            name: String::from(name),
+            from_config: true,
+        };
+
+        assert_eq!(
+            config,
+            Config {
+                input_dir: Some(PathBuf::from("puzzles")),
+                days: Some(vec![1, 2, 3]),
+                format: Some(Format::Csv),
+                timeout: Some(Duration::from_millis(2500)),
+                strategies: vec![
+                    choice(1, "hash-set"),
+                    choice(7, "reverse-index"),
+                    choice(7, "default"),
+                ],
+                cache_dir: Some(PathBuf::from("/tmp/aoc")),
+            }
+        );
+
+        assert_eq!(
+            "days = [8, 1]".parse::<Config>().unwrap().days,
+            Some(vec![8, 1])
+        );
+
+        for text in &[
+            "input = \"x\"",
+            "format = \"yaml\"",
+            "timeout = 0",
+            "days = true",
+            "[strategies]\nx = \"fast\"",
+            "[other]\nkey = 1",
+        ] {
+            assert!(text.parse::<Config>().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_flags_override_config() {
+        let config: Config = indoc! {r#"
+            input-dir = "puzzles"
+            days = "1-3"
+            format = "csv"
+
+            [strategies]
+            1 = "hash-set"
+        "#}
+        .parse()
+        .unwrap();
+
+        let parse = |args: &[&str]| {
+            Args::parse(config.defaults(), args.iter().map(|s| s.to_string())).unwrap()
+        };
+
+        let args = parse(&[]);
+        assert_eq!(args.input_dir, PathBuf::from("puzzles"));
+        assert_eq!(args.days, Some(vec![1, 2, 3]));
+        assert_eq!(args.format, Format::Csv);
+        assert_eq!(args.strategy(1, &["default", "hash-set"]), "hash-set");
+
+        let args = parse(&["-d", "7", "-f", "text", "other", "-s", "1:default"]);
+        assert_eq!(args.input_dir, PathBuf::from("other"));
+        assert_eq!(args.days, Some(vec![7]));
+        assert_eq!(args.format, Format::Text);
+        assert_eq!(args.strategy(1, &["default", "hash-set"]), "default");
+
+        assert_eq!(parse(&["--all"]).days, None);
+    }
+}
@@ -1,18 +1,22 @@
-use anyhow::bail;
+use std::collections::HashSet;
 
-fn parse_input(raw_input: &str) -> anyhow::Result<Vec<i32>> {
-    let values = raw_input
-        .lines()
-        .map(|line| line.parse())
-        .collect::<Result<Vec<_>, _>>()?;
+use anyhow::{anyhow, bail};
 
-    Ok(values)
+use crate::input::PuzzleInput;
+use crate::parse::ParseError;
+use crate::solution::{Solution, Strategy};
+
+pub fn parse_input(input: &PuzzleInput) -> Result<Vec<i32>, ParseError> {
+    input.parse_lines_with(|line| {
+        line.parse()
+            .map_err(|err| ParseError::new(line, line, format!("Invalid number: {}", err)))
+    })
 }
 
-fn find_two_sum_to_2020(values: &[i32]) -> anyhow::Result<(i32, i32)> {
-    for (i, num1) in values.into_iter().enumerate() {
+pub fn find_two_sum_to_2020(values: &[i32]) -> anyhow::Result<(i32, i32)> {
+    for (i, num1) in values.iter().enumerate() {
         for num2 in &values[(i + 1)..] {
-            let sum = num1 + num2;
+            let sum = i64::from(*num1) + i64::from(*num2);
 
             if sum == 2020 {
                 return Ok((*num1, *num2));
@@ -23,11 +27,11 @@ fn find_two_sum_to_2020(values: &[i32]) -> anyhow::Result<(i32, i32)> {
     bail!("Could not find 2 numbers adding 2020!");
 }
 
-fn find_three_sum_to_2020(values: &[i32]) -> anyhow::Result<(i32, i32, i32)> {
-    for (i, num1) in values.into_iter().enumerate() {
-        for (j, num2) in values[(i + 1)..].into_iter().enumerate() {
+pub fn find_three_sum_to_2020(values: &[i32]) -> anyhow::Result<(i32, i32, i32)> {
+    for (i, num1) in values.iter().enumerate() {
+        for (j, num2) in values[(i + 1)..].iter().enumerate() {
             for num3 in &values[(i + j + 1)..] {
-                let sum = num1 + num2 + num3;
+                let sum = i64::from(*num1) + i64::from(*num2) + i64::from(*num3);
                 if sum == 2020 {
                     return Ok((*num1, *num2, *num3));
                 }
@@ -38,24 +42,99 @@ fn find_three_sum_to_2020(values: &[i32]) -> anyhow::Result<(i32, i32, i32)> {
     bail!("Could not find 2 numbers adding 2020!");
 }
 
-pub fn part1(raw_input: &str) -> anyhow::Result<i32> {
-    let values = parse_input(raw_input)?;
+/// Finds a value in `values[start..]` whose partner to make `target` was
+/// seen before it, looking each partner up in a set rather than scanning.
+fn find_pair_with_set(values: &[i32], start: usize, target: i64) -> Option<(i32, i32)> {
+    let mut seen = HashSet::new();
+
+    for num in &values[start..] {
+        let partner = target - i64::from(*num);
+
+        if seen.contains(&partner) {
+            // The partner is one of `values`, so it fits in an `i32`.
+            return Some((partner as i32, *num));
+        }
+
+        seen.insert(i64::from(*num));
+    }
+
+    None
+}
+
+pub fn find_two_sum_to_2020_with_set(values: &[i32]) -> anyhow::Result<(i32, i32)> {
+    find_pair_with_set(values, 0, 2020)
+        .ok_or_else(|| anyhow!("Could not find 2 numbers adding 2020!"))
+}
+
+pub fn find_three_sum_to_2020_with_set(values: &[i32]) -> anyhow::Result<(i32, i32, i32)> {
+    for (i, num1) in values.iter().enumerate() {
+        if let Some((num2, num3)) = find_pair_with_set(values, i + 1, 2020 - i64::from(*num1)) {
+            return Ok((*num1, num2, num3));
+        }
+    }
 
-    let (num1, num2) = find_two_sum_to_2020(&values)?;
+    bail!("Could not find 3 numbers adding 2020!");
+}
 
-    let result = num1 * num2;
+fn product_of_two((num1, num2): (i32, i32)) -> i64 {
+    // Two `i32`s always fit in an `i64`.
+    i64::from(num1) * i64::from(num2)
+}
 
-    Ok(result)
+fn product_of_three((num1, num2, num3): (i32, i32, i32)) -> anyhow::Result<i64> {
+    i64::from(num1)
+        .checked_mul(i64::from(num2))
+        .and_then(|product| product.checked_mul(i64::from(num3)))
+        .ok_or_else(|| anyhow!("{} * {} * {} overflows", num1, num2, num3))
 }
 
-pub fn part2(raw_input: &str) -> anyhow::Result<i32> {
-    let values = parse_input(raw_input)?;
+pub fn part1(values: &[i32]) -> anyhow::Result<i64> {
+    Ok(product_of_two(find_two_sum_to_2020(values)?))
+}
 
-    let (num1, num2, num3) = find_three_sum_to_2020(&values)?;
+pub fn part2(values: &[i32]) -> anyhow::Result<i64> {
+    product_of_three(find_three_sum_to_2020(values)?)
+}
 
-    let result = num1 * num2 * num3;
+pub fn part1_with_set(values: &[i32]) -> anyhow::Result<i64> {
+    Ok(product_of_two(find_two_sum_to_2020_with_set(values)?))
+}
 
-    Ok(result)
+pub fn part2_with_set(values: &[i32]) -> anyhow::Result<i64> {
+    product_of_three(find_three_sum_to_2020_with_set(values)?)
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u32 = 1;
+    const NAME: &'static str = "Report Repair";
+
+    type Input = Vec<i32>;
+    type Output1 = i64;
+    type Output2 = i64;
+
+    const STRATEGIES1: &'static [Strategy<Self::Input, Self::Output1>] = &[Strategy {
+        name: "hash-set",
+        solve: |values| part1_with_set(values),
+    }];
+
+    const STRATEGIES2: &'static [Strategy<Self::Input, Self::Output2>] = &[Strategy {
+        name: "hash-set",
+        solve: |values| part2_with_set(values),
+    }];
+
+    fn parse(input: &PuzzleInput) -> anyhow::Result<Self::Input> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> anyhow::Result<Self::Output1> {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> anyhow::Result<Self::Output2> {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
@@ -72,20 +151,54 @@ mod tests {
             34
          "};
 
-        assert_eq!(parse_input(input).unwrap(), vec![12, 23, 34]);
+        assert_eq!(
+            parse_input(&PuzzleInput::new(input)).unwrap(),
+            vec![12, 23, 34]
+        );
+        assert_eq!(
+            parse_input(&PuzzleInput::new("12\r\n23 \r\n")).unwrap(),
+            vec![12, 23]
+        );
+
+        let error = parse_input(&PuzzleInput::new("12\n2x3\n")).unwrap_err();
+
+        assert_eq!((error.line, error.columns), (2, 0..3));
     }
 
     #[test]
     fn test_find_two_sum_to_2020() {
-        let values = [1721, 979, 366, 299, 675, 1456];
+        let values =
+            parse_input(&PuzzleInput::new(include_str!("../examples/day1/1.txt"))).unwrap();
 
         assert_eq!(find_two_sum_to_2020(&values).unwrap(), (1721, 299));
     }
 
     #[test]
     fn test_find_three_sum_to_2020() {
-        let values = [1721, 979, 366, 299, 675, 1456];
+        let values =
+            parse_input(&PuzzleInput::new(include_str!("../examples/day1/1.txt"))).unwrap();
 
         assert_eq!(find_three_sum_to_2020(&values).unwrap(), (979, 366, 675));
     }
+
+    #[test]
+    fn test_find_sums_with_set() {
+        let values =
+            parse_input(&PuzzleInput::new(include_str!("../examples/day1/1.txt"))).unwrap();
+
+        assert_eq!(find_two_sum_to_2020_with_set(&values).unwrap(), (1721, 299));
+        assert_eq!(
+            find_three_sum_to_2020_with_set(&values).unwrap(),
+            (979, 366, 675)
+        );
+        assert!(find_two_sum_to_2020_with_set(&[1010]).is_err());
+    }
+
+    #[test]
+    fn test_large_values() {
+        let values = [2_000_000_000, -1_999_997_980, 1_000_000_000, 1_020];
+
+        assert_eq!(part1(&values).unwrap(), -3_999_995_960_000_000_000);
+        assert!(part2(&[2_000_000_000, -2_000_000_000, 2_020]).is_err());
+    }
 }
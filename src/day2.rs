@@ -1,59 +1,67 @@
 use std::str::FromStr;
 
+use crate::input::PuzzleInput;
+use crate::parse::ParseError;
+use crate::solution::Solution;
+
 #[derive(Debug, PartialEq)]
-struct Entry {
-    range: (usize, usize),
-    character: char,
-    password: String,
+pub struct Entry {
+    pub range: (usize, usize),
+    pub character: char,
+    pub password: String,
 }
 
-fn split_components(s: &str) -> anyhow::Result<(&str, &str, &str)> {
+fn split_components(s: &str) -> Result<(&str, &str, &str), ParseError> {
     let mut parts = s.split_whitespace();
 
-    Ok((
-        parts
-            .next()
-            .ok_or_else(|| anyhow::anyhow!("Could not parse range"))?,
-        parts
-            .next()
-            .ok_or_else(|| anyhow::anyhow!("Could not parse character"))?,
+    let mut next = |name: &str| {
         parts
             .next()
-            .ok_or_else(|| anyhow::anyhow!("Could not parse password"))?,
-    ))
+            .ok_or_else(|| ParseError::new(s, &s[s.len()..], format!("Could not parse {}", name)))
+    };
+
+    Ok((next("range")?, next("character")?, next("password")?))
 }
 
-fn parse_range(s: &str) -> anyhow::Result<(usize, usize)> {
+fn parse_range(s: &str) -> Result<(usize, usize), ParseError> {
     let mut parts = s.split('-');
 
-    let begin = parts
-        .next()
-        .ok_or_else(|| anyhow::anyhow!("Could not parse beginning of range"))?
-        .parse::<usize>()?;
+    let mut next = |name: &str| {
+        let part = parts.next().ok_or_else(|| {
+            ParseError::new(
+                s,
+                &s[s.len()..],
+                format!("Could not parse {} of range", name),
+            )
+        })?;
 
-    let end = parts
-        .next()
-        .ok_or_else(|| anyhow::anyhow!("Could not parse end of range"))?
-        .parse::<usize>()?;
+        part.parse::<usize>()
+            .map_err(|err| ParseError::new(s, part, format!("Invalid {} of range: {}", name, err)))
+    };
+
+    let begin = next("beginning")?;
+
+    let end = next("end")?;
 
     Ok((begin, end))
 }
 
-fn parse_character(s: &str) -> anyhow::Result<char> {
+fn parse_character(s: &str) -> Result<char, ParseError> {
     s.chars()
-        .nth(0)
-        .ok_or_else(|| anyhow::anyhow!("Could not parse character"))
+        .next()
+        .ok_or_else(|| ParseError::new(s, s, "Could not parse character"))
 }
 
 impl FromStr for Entry {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (range_str, character_str, password_str) = split_components(s)?;
 
-        let range = parse_range(range_str)?;
+        let range = parse_range(range_str).map_err(|err| err.within(s, range_str))?;
 
-        let character = parse_character(character_str)?;
+        let character =
+            parse_character(character_str).map_err(|err| err.within(s, character_str))?;
 
         let password = String::from(password_str);
 
@@ -66,7 +74,7 @@ impl FromStr for Entry {
 }
 
 impl Entry {
-    fn is_valid_part1(&self) -> bool {
+    pub fn is_valid_part1(&self) -> bool {
         let count = count_occurances(self.character, &self.password);
 
         let (start, end) = self.range;
@@ -74,7 +82,7 @@ impl Entry {
         (start..=end).contains(&count)
     }
 
-    fn is_valid_part2(&self) -> bool {
+    pub fn is_valid_part2(&self) -> bool {
         let (first, second) = self.range;
 
         let count = self.password.char_indices().filter(|(i, c)| {
@@ -96,37 +104,49 @@ fn count_occurances(character: char, s: &str) -> usize {
     s.chars().filter(|&c| c == character).count()
 }
 
-fn parse_input(input: &str) -> anyhow::Result<Vec<Entry>> {
-    let entries = input
-        .lines()
-        .map(Entry::from_str)
-        .collect::<Result<_, _>>()?;
-
-    Ok(entries)
+pub fn parse_input(input: &PuzzleInput) -> Result<Vec<Entry>, ParseError> {
+    input.parse_lines()
 }
 
-pub fn part1(raw_input: &str) -> anyhow::Result<usize> {
-    let input = parse_input(raw_input)?;
-
+pub fn part1(input: &[Entry]) -> anyhow::Result<usize> {
     let num_valid = input.iter().filter(|entry| entry.is_valid_part1()).count();
 
     Ok(num_valid)
 }
 
-pub fn part2(raw_input: &str) -> anyhow::Result<usize> {
-    let input = parse_input(raw_input)?;
-
+pub fn part2(input: &[Entry]) -> anyhow::Result<usize> {
     let num_valid = input.iter().filter(|entry| entry.is_valid_part2()).count();
 
     Ok(num_valid)
 }
 
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u32 = 2;
+    const NAME: &'static str = "Password Philosophy";
+
+    type Input = Vec<Entry>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &PuzzleInput) -> anyhow::Result<Self::Input> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> anyhow::Result<Self::Output1> {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> anyhow::Result<Self::Output2> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use indoc::indoc;
-
     #[test]
     fn test_parse_entry() {
         let entry = "5-12 c: abcdefg".parse::<Entry>();
@@ -141,6 +161,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_entry_error() {
+        let error = parse_input(&PuzzleInput::new("1-3 a: abcde\n1-x b: cdefg\n")).unwrap_err();
+
+        assert_eq!(error.line, 2);
+        assert_eq!(error.columns, 2..3);
+        assert_eq!(error.text, "1-x b: cdefg");
+
+        let error = "1-3 a:".parse::<Entry>().unwrap_err();
+
+        assert_eq!(error.message, "Could not parse password");
+        assert_eq!(error.columns, 6..6);
+    }
+
     #[test]
     fn test_count_occurances() {
         assert_eq!(count_occurances('b', "abcdbbe"), 3);
@@ -202,11 +236,7 @@ mod tests {
 
     #[test]
     fn test_parse_input() {
-        let input = indoc! {"
-            1-3 a: abcde
-            1-3 b: cdefg
-            2-9 c: ccccccccc
-        "};
+        let input = include_str!("../examples/day2/1.txt");
 
         let expected = vec![
             Entry {
@@ -226,6 +256,15 @@ mod tests {
             },
         ];
 
-        assert_eq!(parse_input(input).unwrap(), expected);
+        assert_eq!(parse_input(&PuzzleInput::new(input)).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_large_input() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg\n".repeat(50_000) + "1-18446744073709551615 a: a\n";
+        let entries = parse_input(&PuzzleInput::new(&input)).unwrap();
+
+        assert_eq!(part1(&entries).unwrap(), 50_001);
+        assert_eq!(part2(&entries).unwrap(), 50_001);
     }
 }
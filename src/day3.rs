@@ -1,22 +1,31 @@
-use std::convert::{TryFrom, TryInto as _};
+use std::convert::TryFrom;
 use std::str::FromStr;
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
+
+use crate::input::PuzzleInput;
+use crate::parse::ParseError;
+use crate::solution::Solution;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
-enum Square {
+pub enum Square {
     Open,
     Tree,
 }
 
 impl TryFrom<char> for Square {
-    type Error = anyhow::Error;
+    type Error = ParseError;
 
     fn try_from(c: char) -> Result<Self, Self::Error> {
         let square = match c {
             '.' => Self::Open,
             '#' => Self::Tree,
-            invalid => bail!("Invalid square character: {}", invalid),
+            invalid => {
+                let text = invalid.to_string();
+                let message = format!("Invalid square character: {}", invalid);
+
+                return Err(ParseError::new(&text, &text, message));
+            }
         };
 
         Ok(square)
@@ -24,25 +33,29 @@ impl TryFrom<char> for Square {
 }
 
 #[derive(PartialEq, Debug)]
-struct TreeMap {
+pub struct TreeMap {
     squares: Vec<Square>,
     width: usize,
     height: usize,
 }
 
 impl TreeMap {
-    fn get(&self, row: usize, mut col: usize) -> Option<Square> {
-        // Adjust the column for the infinite width of the forest.
-        while col >= self.width {
-            col -= self.width;
-        }
+    pub fn width(&self) -> usize {
+        self.width
+    }
 
-        let index = col + (row * self.width);
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<Square> {
+        // Adjust the column for the infinite width of the forest.
+        let index = (col % self.width) + (row * self.width);
 
         self.squares.get(index).copied()
     }
 
-    fn count_trees_hit(&self, right_step: usize, down_step: usize) -> usize {
+    pub fn count_trees_hit(&self, right_step: usize, down_step: usize) -> usize {
         let mut row = down_step;
         let mut col = right_step;
 
@@ -62,7 +75,7 @@ impl TreeMap {
 }
 
 #[derive(Default)]
-struct TreeMapBuilder {
+pub struct TreeMapBuilder {
     squares: Vec<Square>,
     width: Option<usize>,
     height: usize,
@@ -70,14 +83,14 @@ struct TreeMapBuilder {
 }
 
 impl TreeMapBuilder {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             valid: true,
             ..Default::default()
         }
     }
 
-    fn row(mut self, row: &[Square]) -> Self {
+    pub fn row(mut self, row: &[Square]) -> Self {
         if !self.valid {
             return self;
         }
@@ -99,14 +112,21 @@ impl TreeMapBuilder {
         self
     }
 
-    fn build(self) -> anyhow::Result<TreeMap> {
+    pub fn build(self) -> anyhow::Result<TreeMap> {
         if !self.valid {
             bail!("Invalid tree map");
         }
 
+        // Every column wraps around the width, so it can't be zero.
+        let width = match self.width {
+            None => bail!("Tree map has no rows"),
+            Some(0) => bail!("Tree map rows are empty"),
+            Some(width) => width,
+        };
+
         let tree_map = TreeMap {
             squares: self.squares,
-            width: self.width.unwrap_or_default(),
+            width,
             height: self.height,
         };
 
@@ -115,56 +135,101 @@ impl TreeMapBuilder {
 }
 
 impl TreeMap {
-    fn builder() -> TreeMapBuilder {
+    pub fn builder() -> TreeMapBuilder {
         TreeMapBuilder::new()
     }
 }
 
 impl FromStr for TreeMap {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut builder = TreeMap::builder();
-
-        let rows = s.lines().map(|line| {
-            line.chars()
-                .map(|c| c.try_into())
-                .collect::<Result<Vec<_>, _>>()
-        });
-
-        for row in rows {
-            let row = row?;
+        parse_input(&PuzzleInput::new(s))
+    }
+}
 
+/// Reads the map a row at a time. A lenient input skips rows with unknown
+/// squares or the wrong width.
+pub fn parse_input(input: &PuzzleInput) -> Result<TreeMap, ParseError> {
+    let s = input.as_str();
+
+    let mut builder = TreeMap::builder();
+    let mut width = None;
+
+    for line in input.lines() {
+        let row = line
+            .char_indices()
+            .map(|(i, c)| {
+                let square = &line[i..(i + c.len_utf8())];
+
+                Square::try_from(c).map_err(|err| err.within(s, square))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|row| {
+                // Checked here rather than left to the builder so the error
+                // can say which row is the odd one out.
+                let expected = *width.get_or_insert(row.len());
+
+                if row.len() == expected {
+                    Ok(row)
+                } else {
+                    let message = format!("Expected {} squares, found {}", expected, row.len());
+
+                    Err(ParseError::new(s, line, message))
+                }
+            });
+
+        if let Some(row) = input.recover(row)? {
             builder = builder.row(&row);
         }
-
-        let tree_map = builder.build()?;
-
-        Ok(tree_map)
     }
-}
 
-pub fn part1(raw_input: &str) -> anyhow::Result<usize> {
-    let tree_map: TreeMap = raw_input.parse()?;
+    builder
+        .build()
+        .map_err(|err| ParseError::new(s, s, err.to_string()))
+}
 
+pub fn part1(tree_map: &TreeMap) -> anyhow::Result<usize> {
     let trees_hit = tree_map.count_trees_hit(3, 1);
 
     Ok(trees_hit)
 }
 
-pub fn part2(raw_input: &str) -> anyhow::Result<usize> {
-    let tree_map: TreeMap = raw_input.parse()?;
-
+pub fn part2(tree_map: &TreeMap) -> anyhow::Result<usize> {
     let slopes: [(usize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
 
     let answer = slopes
         .iter()
         .map(|(r, d)| tree_map.count_trees_hit(*r, *d))
-        .product();
+        .try_fold(1_usize, |product, trees_hit| product.checked_mul(trees_hit))
+        .ok_or_else(|| anyhow!("Product of trees hit overflows"))?;
 
     Ok(answer)
 }
 
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u32 = 3;
+    const NAME: &'static str = "Toboggan Trajectory";
+
+    type Input = TreeMap;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &PuzzleInput) -> anyhow::Result<Self::Input> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> anyhow::Result<Self::Output1> {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> anyhow::Result<Self::Output2> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -197,6 +262,51 @@ mod tests {
         };
 
         assert_eq!(input.parse::<TreeMap>().unwrap(), expected);
+        assert_eq!("..##\r\n#...\r\n.#..".parse::<TreeMap>().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_tree_map_errors() {
+        let error = "..#\n.x.\n".parse::<TreeMap>().unwrap_err();
+
+        assert_eq!(error.message, "Invalid square character: x");
+        assert_eq!((error.line, error.columns), (2, 1..2));
+
+        let error = "..#\n....\n".parse::<TreeMap>().unwrap_err();
+
+        assert_eq!(error.message, "Expected 3 squares, found 4");
+        assert_eq!((error.line, error.columns), (2, 0..4));
+    }
+
+    #[test]
+    fn test_parse_tree_map_lenient() {
+        let input = PuzzleInput::lenient("..#\n.x.\n....\n#..\n");
+        let tree_map = parse_input(&input).unwrap();
+
+        assert_eq!(tree_map.height(), 2);
+
+        let lines: Vec<_> = input.take_rejected().iter().map(|err| err.line).collect();
+        assert_eq!(lines, vec![2, 3]);
+
+        let input = PuzzleInput::lenient("abc\nxyz\n");
+        let error = parse_input(&input).unwrap_err();
+
+        assert_eq!(error.message, "Tree map has no rows");
+        assert_eq!(input.take_rejected().len(), 2);
+
+        let input = PuzzleInput::lenient("\n\n#\n");
+        let error = parse_input(&input).unwrap_err();
+
+        assert_eq!(error.message, "Tree map rows are empty");
+    }
+
+    #[test]
+    fn test_parse_empty_tree_map() {
+        let error = "".parse::<TreeMap>().unwrap_err();
+
+        assert_eq!(error.message, "Tree map has no rows");
+        assert!(TreeMap::builder().build().is_err());
+        assert!(TreeMap::builder().row(&[]).build().is_err());
     }
 
     #[test]
@@ -240,22 +350,21 @@ mod tests {
 
     #[test]
     fn test_trees_hit() {
-        let tree_map = indoc! {"
-            ..##.......
-            #...#...#..
-            .#....#..#.
-            ..#.#...#.#
-            .#...##..#.
-            ..#.##.....
-            .#.#.#....#
-            .#........#
-            #.##...#...
-            #...##....#
-            .#..#...#.#
-        "}
-        .parse::<TreeMap>()
-        .unwrap();
+        let tree_map = include_str!("../examples/day3/1.txt")
+            .parse::<TreeMap>()
+            .unwrap();
 
         assert_eq!(tree_map.count_trees_hit(3, 1), 7);
     }
+
+    #[test]
+    fn test_large_product() {
+        let tree_map = "#\n".repeat(100).parse::<TreeMap>().unwrap();
+        assert_eq!(part2(&tree_map).unwrap(), 99 * 99 * 99 * 99 * 49);
+
+        // Every slope hits a tree on each row it visits, and the product
+        // no longer fits.
+        let tree_map = "#\n".repeat(10_000).parse::<TreeMap>().unwrap();
+        assert!(part2(&tree_map).is_err());
+    }
 }
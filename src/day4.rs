@@ -3,21 +3,23 @@ use std::convert::{TryFrom, TryInto as _};
 use std::fmt;
 use std::str::FromStr;
 
-use anyhow::bail;
-
-const BIRTH_YEAR: &'static str = "byr";
-const ISSUE_YEAR: &'static str = "iyr";
-const EXPIRATION_YEAR: &'static str = "eyr";
-const HEIGHT: &'static str = "hgt";
-const HAIR_COLOR: &'static str = "hcl";
-const EYE_COLOR: &'static str = "ecl";
-const ID: &'static str = "pid";
-const COUNTRY_ID: &'static str = "cid";
+use crate::input::PuzzleInput;
+use crate::parse::ParseError;
+use crate::solution::Solution;
+
+const BIRTH_YEAR: &str = "byr";
+const ISSUE_YEAR: &str = "iyr";
+const EXPIRATION_YEAR: &str = "eyr";
+const HEIGHT: &str = "hgt";
+const HAIR_COLOR: &str = "hcl";
+const EYE_COLOR: &str = "ecl";
+const ID: &str = "pid";
+const COUNTRY_ID: &str = "cid";
 
 macro_rules! year {
     ($year:ident, $range:pat) => {
         #[derive(Debug, Clone, Copy, PartialEq)]
-        struct $year(i32);
+        pub struct $year(pub i32);
 
         impl TryFrom<i32> for $year {
             type Error = anyhow::Error;
@@ -31,10 +33,16 @@ macro_rules! year {
         }
 
         impl FromStr for $year {
-            type Err = anyhow::Error;
+            type Err = ParseError;
 
             fn from_str(s: &str) -> Result<Self, Self::Err> {
-                Ok(s.parse::<i32>()?.try_into()?)
+                let value = s
+                    .parse::<i32>()
+                    .map_err(|err| ParseError::new(s, s, format!("Invalid year: {}", err)))?;
+
+                value
+                    .try_into()
+                    .map_err(|err: anyhow::Error| ParseError::new(s, s, err.to_string()))
             }
         }
     };
@@ -45,19 +53,19 @@ year!(IssueYear, 2010..=2020);
 year!(ExpirationYear, 2020..=2030);
 
 #[derive(PartialEq, Debug, Clone, Copy)]
-enum LengthUnit {
+pub enum LengthUnit {
     Centimeters,
     Inches,
 }
 
 impl FromStr for LengthUnit {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let unit = match s {
             "cm" => Self::Centimeters,
             "in" => Self::Inches,
-            _ => bail!("Unrecognized unit: {}", s),
+            _ => return Err(ParseError::new(s, s, format!("Unrecognized unit: {}", s))),
         };
 
         Ok(unit)
@@ -75,64 +83,76 @@ impl fmt::Display for LengthUnit {
 }
 
 #[derive(PartialEq, Debug, Clone, Copy)]
-struct Height {
-    value: i32,
-    unit: LengthUnit,
+pub struct Height {
+    pub value: i32,
+    pub unit: LengthUnit,
 }
 
 impl FromStr for Height {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let i = s.len() - 2;
-        let unit = s
+        // Anything shorter than a unit fails to parse as one below.
+        let i = s.len().saturating_sub(2);
+        let unit_str = s
             .get(i..)
-            .ok_or_else(|| anyhow::anyhow!("Could not parse height unit"))?
-            .parse()?;
+            .ok_or_else(|| ParseError::new(s, s, "Could not parse height unit"))?;
 
-        let value: i32 = s
+        let unit: LengthUnit = unit_str
+            .parse()
+            .map_err(|err: ParseError| err.within(s, unit_str))?;
+
+        let value_str = s
             .get(..i)
-            .ok_or_else(|| anyhow::anyhow!("Could not parse height value"))?
-            .parse()?;
+            .ok_or_else(|| ParseError::new(s, s, "Could not parse height value"))?;
+
+        let value: i32 = value_str
+            .parse()
+            .map_err(|err| ParseError::new(s, value_str, format!("Invalid height: {}", err)))?;
 
         let range = match unit {
-            LengthUnit::Centimeters => (150..=193),
-            LengthUnit::Inches => (59..=76),
+            LengthUnit::Centimeters => 150..=193,
+            LengthUnit::Inches => 59..=76,
         };
 
         if range.contains(&value) {
             Ok(Self { value, unit })
         } else {
-            bail!("Value {} outside of range for unit {}", value, unit);
+            let message = format!("Value {} outside of range for unit {}", value, unit);
+
+            Err(ParseError::new(s, value_str, message))
         }
     }
 }
 
 #[derive(PartialEq, Debug, Clone, Copy)]
-struct HairColor(i32);
+pub struct HairColor(pub i32);
 
 impl FromStr for HairColor {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if !s.starts_with('#') {
-            bail!("Hair color must start with a '#'");
+            return Err(ParseError::new(s, s, "Hair color must start with a '#'"));
         }
 
-        let rest = s.get(1..).ok_or_else(|| anyhow::anyhow!("Cannot parse rest of hair color"))?;
+        let rest = s
+            .get(1..)
+            .ok_or_else(|| ParseError::new(s, s, "Cannot parse rest of hair color"))?;
 
         if rest.len() != 6 {
-            bail!("Hair color must be 6 digits");
+            return Err(ParseError::new(s, rest, "Hair color must be 6 digits"));
         }
 
-        let value = i32::from_str_radix(rest, 16)?;
+        let value = i32::from_str_radix(rest, 16)
+            .map_err(|err| ParseError::new(s, rest, format!("Invalid hair color: {}", err)))?;
 
         Ok(Self(value))
     }
 }
 
 #[derive(PartialEq, Debug, Clone, Copy)]
-enum EyeColor {
+pub enum EyeColor {
     Amber,
     Blue,
     Brown,
@@ -143,7 +163,7 @@ enum EyeColor {
 }
 
 impl FromStr for EyeColor {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let color = match s {
@@ -154,7 +174,13 @@ impl FromStr for EyeColor {
             "grn" => Self::Green,
             "hzl" => Self::Hazel,
             "oth" => Self::Other,
-            _ => bail!("Unrecognized eye color: {}", s),
+            _ => {
+                return Err(ParseError::new(
+                    s,
+                    s,
+                    format!("Unrecognized eye color: {}", s),
+                ))
+            }
         };
 
         Ok(color)
@@ -162,34 +188,62 @@ impl FromStr for EyeColor {
 }
 
 #[derive(PartialEq, Debug, Clone, Copy)]
-struct PassportId(i32);
+pub struct PassportId(pub i32);
 
 impl FromStr for PassportId {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.len() != 9 {
-            bail!("Passport id must be 9 digits");
+            return Err(ParseError::new(s, s, "Passport id must be 9 digits"));
         }
 
-        Ok(Self(s.parse()?))
+        let id = s
+            .parse()
+            .map_err(|err| ParseError::new(s, s, format!("Invalid passport id: {}", err)))?;
+
+        Ok(Self(id))
     }
 }
 
 #[derive(PartialEq, Debug)]
-struct Passport {
-    birth_year: BirthYear,
-    issue_year: IssueYear,
-    expiration_year: ExpirationYear,
-    height: Height,
-    hair_color: HairColor,
-    eye_color: EyeColor,
-    id: PassportId,
-    country_id: Option<String>,
+pub struct Passport {
+    pub birth_year: BirthYear,
+    pub issue_year: IssueYear,
+    pub expiration_year: ExpirationYear,
+    pub height: Height,
+    pub hair_color: HairColor,
+    pub eye_color: EyeColor,
+    pub id: PassportId,
+    pub country_id: Option<String>,
+}
+
+/// Finds the value of `key` among the fields of the passport `s`.
+fn get_field<'a>(
+    s: &'a str,
+    items: &HashMap<&str, &'a str>,
+    key: &str,
+) -> Result<&'a str, ParseError> {
+    items
+        .get(key)
+        .copied()
+        .ok_or_else(|| ParseError::new(s, s, format!("No value found for {}", key)))
+}
+
+/// Parses the value of `key`, reporting errors against the whole passport.
+fn parse_field<T>(s: &str, items: &HashMap<&str, &str>, key: &str) -> Result<T, ParseError>
+where
+    T: FromStr<Err = ParseError>,
+{
+    let value = get_field(s, items, key)?;
+
+    value
+        .parse()
+        .map_err(|err: ParseError| err.within(s, value))
 }
 
 impl FromStr for Passport {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let items: HashMap<_, _> = s
@@ -201,22 +255,14 @@ impl FromStr for Passport {
             })
             .collect();
 
-        let get_str = |key| -> anyhow::Result<&str> {
-            let value: &&str = items
-                .get(key)
-                .ok_or_else(|| anyhow::anyhow!("No value found for {}", key))?;
-
-            Ok(*value)
-        };
-
         let passport = Passport {
-            birth_year: get_str(BIRTH_YEAR)?.parse()?,
-            issue_year: get_str(ISSUE_YEAR)?.parse()?,
-            expiration_year: get_str(EXPIRATION_YEAR)?.parse()?,
-            height: get_str(HEIGHT)?.parse()?,
-            hair_color: get_str(HAIR_COLOR)?.parse()?,
-            eye_color: get_str(EYE_COLOR)?.parse()?,
-            id: get_str(ID)?.parse()?,
+            birth_year: parse_field(s, &items, BIRTH_YEAR)?,
+            issue_year: parse_field(s, &items, ISSUE_YEAR)?,
+            expiration_year: parse_field(s, &items, EXPIRATION_YEAR)?,
+            height: parse_field(s, &items, HEIGHT)?,
+            hair_color: parse_field(s, &items, HAIR_COLOR)?,
+            eye_color: parse_field(s, &items, EYE_COLOR)?,
+            id: parse_field(s, &items, ID)?,
             country_id: items.get(COUNTRY_ID).map(|s| String::from(*s)),
         };
 
@@ -225,19 +271,19 @@ impl FromStr for Passport {
 }
 
 #[derive(PartialEq, Debug)]
-struct SimplePassport {
-    birth_year: String,
-    issue_year: String,
-    expiration_year: String,
-    height: String,
-    hair_color: String,
-    eye_color: String,
-    id: String,
-    country_id: Option<String>,
+pub struct SimplePassport {
+    pub birth_year: String,
+    pub issue_year: String,
+    pub expiration_year: String,
+    pub height: String,
+    pub hair_color: String,
+    pub eye_color: String,
+    pub id: String,
+    pub country_id: Option<String>,
 }
 
 impl FromStr for SimplePassport {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let items: HashMap<_, _> = s
@@ -249,13 +295,7 @@ impl FromStr for SimplePassport {
             })
             .collect();
 
-        let get_str = |key| -> anyhow::Result<&str> {
-            let value: &&str = items
-                .get(key)
-                .ok_or_else(|| anyhow::anyhow!("No value found for {}", key))?;
-
-            Ok(*value)
-        };
+        let get_str = |key| get_field(s, &items, key);
 
         let passport = Self {
             birth_year: get_str(BIRTH_YEAR)?.to_string(),
@@ -272,20 +312,47 @@ impl FromStr for SimplePassport {
     }
 }
 
-pub fn part1(raw_input: &str) -> anyhow::Result<usize> {
-    Ok(raw_input
-        .split("\n\n")
+pub fn parse_input(input: &PuzzleInput) -> Result<Vec<String>, ParseError> {
+    Ok(input.blank_line_groups().map(String::from).collect())
+}
+
+pub fn part1(sequences: &[String]) -> anyhow::Result<usize> {
+    Ok(sequences
+        .iter()
         .filter_map(|sequence| sequence.parse::<SimplePassport>().ok())
         .count())
 }
 
-pub fn part2(raw_input: &str) -> anyhow::Result<usize> {
-    Ok(raw_input
-        .split("\n\n")
+pub fn part2(sequences: &[String]) -> anyhow::Result<usize> {
+    Ok(sequences
+        .iter()
         .filter_map(|sequence| sequence.parse::<Passport>().ok())
         .count())
 }
 
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u32 = 4;
+    const NAME: &'static str = "Passport Processing";
+
+    type Input = Vec<String>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &PuzzleInput) -> anyhow::Result<Self::Input> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> anyhow::Result<Self::Output1> {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> anyhow::Result<Self::Output2> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,9 +384,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_passport_error() {
+        let input = indoc! {"
+            ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+            byr:1937 iyr:2017 cid:147 hgt:183xx
+        "};
+
+        let error = input.parse::<Passport>().unwrap_err();
+
+        assert_eq!(error.message, "Unrecognized unit: xx");
+        assert_eq!((error.line, error.columns), (2, 33..35));
+    }
+
+    #[test]
+    fn test_parse_input_crlf() {
+        let input = include_str!("../examples/day4/1.txt").replace('\n', "\r\n");
+        let passports = parse_input(&PuzzleInput::new(&input)).unwrap();
+
+        assert_eq!(passports.len(), 4);
+        assert_eq!(part1(&passports).unwrap(), 2);
+    }
+
     #[test]
     fn test_parse_birth_year() {
         assert_eq!(BirthYear::from_str("2000").unwrap(), BirthYear(2000));
         assert!(BirthYear::from_str("5").is_err());
     }
+
+    #[test]
+    fn test_parse_short_height() {
+        assert_eq!(
+            Height::from_str("x").unwrap_err().message,
+            "Unrecognized unit: x"
+        );
+        assert!(Height::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_large_input() {
+        let valid = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:1937 iyr:2017 hgt:183cm\n\n";
+        let invalid = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:1937 iyr:2017 hgt:183\n\n";
+
+        let input = valid.repeat(4_000) + &invalid.repeat(2_000);
+        let passports = parse_input(&PuzzleInput::new(&input)).unwrap();
+
+        assert_eq!(part1(&passports).unwrap(), 6_000);
+        assert_eq!(part2(&passports).unwrap(), 4_000);
+    }
 }
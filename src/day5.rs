@@ -4,80 +4,118 @@ use std::collections::BTreeSet;
 
 use anyhow::bail;
 
+use crate::input::PuzzleInput;
+use crate::parse::ParseError;
+use crate::solution::Solution;
+
 #[derive(Clone, Copy, Debug, PartialEq)]
-enum Fb {
+pub enum Fb {
     Forward,
     Back,
 }
 
 impl TryFrom<char> for Fb {
-    type Error = anyhow::Error;
+    type Error = ParseError;
 
     fn try_from(c: char) -> Result<Self, Self::Error> {
         Ok(match c {
             'F' => Self::Forward,
             'B' => Self::Back,
-            _ => bail!("Unexpected f/b character: {}", c),
+            _ => {
+                let text = c.to_string();
+
+                return Err(ParseError::new(
+                    &text,
+                    &text,
+                    format!("Unexpected f/b character: {}", c),
+                ));
+            }
         })
     }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-enum Rl {
+pub enum Rl {
     Right,
     Left,
 }
 
 impl TryFrom<char> for Rl {
-    type Error = anyhow::Error;
+    type Error = ParseError;
 
     fn try_from(c: char) -> Result<Self, Self::Error> {
         Ok(match c {
             'R' => Self::Right,
             'L' => Self::Left,
-            _ => bail!("Unexpected r/l character: {}", c),
+            _ => {
+                let text = c.to_string();
+
+                return Err(ParseError::new(
+                    &text,
+                    &text,
+                    format!("Unexpected r/l character: {}", c),
+                ));
+            }
         })
     }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-struct Seat {
-    row: i32,
-    col: i32,
+pub struct Seat {
+    pub row: i32,
+    pub col: i32,
 }
 
 impl Seat {
-    fn id(&self) -> i32 {
+    pub fn id(&self) -> i32 {
         (self.row * 8) + self.col
     }
 }
 
 #[derive(Clone, Debug, PartialEq)]
-struct BoardingPass {
-    fbs: [Fb; 7],
-    rls: [Rl; 3],
+pub struct BoardingPass {
+    pub fbs: [Fb; 7],
+    pub rls: [Rl; 3],
+}
+
+/// Parses each character of `s` with `parse`, pointing errors at the
+/// offending character.
+fn parse_chars<T, F>(s: &str, chars: &[(usize, char)], parse: F) -> Result<Vec<T>, ParseError>
+where
+    F: Fn(char) -> Result<T, ParseError>,
+{
+    chars
+        .iter()
+        .map(|&(i, c)| parse(c).map_err(|err| err.within(s, &s[i..(i + c.len_utf8())])))
+        .collect()
 }
 
 impl FromStr for BoardingPass {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let fbs = s
-            .chars()
-            .take(7)
-            .map(Fb::try_from)
-            .collect::<Result<Vec<_>, _>>()?
+        let chars: Vec<_> = s.char_indices().collect();
+
+        let (fb_chars, rest) = chars.split_at(chars.len().min(7));
+        let rl_chars = &rest[..rest.len().min(3)];
+
+        let too_short = || {
+            ParseError::new(
+                s,
+                &s[s.len()..],
+                "Boarding pass is shorter than 10 characters",
+            )
+        };
+
+        let fbs = parse_chars(s, fb_chars, Fb::try_from)?
             .as_slice()
-            .try_into()?;
-
-        let rls = s
-            .chars()
-            .skip(7)
-            .take(3)
-            .map(Rl::try_from)
-            .collect::<Result<Vec<_>, _>>()?
+            .try_into()
+            .map_err(|_| too_short())?;
+
+        let rls = parse_chars(s, rl_chars, Rl::try_from)?
             .as_slice()
-            .try_into()?;
+            .try_into()
+            .map_err(|_| too_short())?;
 
         Ok(Self { fbs, rls })
     }
@@ -91,13 +129,13 @@ fn binary_partition<T: PartialEq>(values: &[T], length: usize, high_value: T) ->
         .map(|(i, _half)| {
             let exponent = length - 1 - i;
             let exponent = exponent.try_into().unwrap();
-            (2 as i32).pow(exponent)
+            2_i32.pow(exponent)
         })
         .sum()
 }
 
 impl BoardingPass {
-    fn find_seat(&self) -> Seat {
+    pub fn find_seat(&self) -> Seat {
         let row = binary_partition(&self.fbs, self.fbs.len(), Fb::Back);
         let col = binary_partition(&self.rls, self.rls.len(), Rl::Right);
 
@@ -105,12 +143,11 @@ impl BoardingPass {
     }
 }
 
-pub fn part1(raw_input: &str) -> anyhow::Result<i32> {
-    let passes: Vec<_> = raw_input
-        .lines()
-        .map(|line| line.parse::<BoardingPass>())
-        .collect::<Result<_, _>>()?;
+pub fn parse_input(input: &PuzzleInput) -> Result<Vec<BoardingPass>, ParseError> {
+    input.parse_lines()
+}
 
+pub fn part1(passes: &[BoardingPass]) -> anyhow::Result<i32> {
     let max_id = passes
         .iter()
         .map(|pass| pass.find_seat().id())
@@ -120,12 +157,7 @@ pub fn part1(raw_input: &str) -> anyhow::Result<i32> {
     Ok(max_id)
 }
 
-pub fn part2(raw_input: &str) -> anyhow::Result<i32> {
-    let passes: Vec<_> = raw_input
-        .lines()
-        .map(|line| line.parse::<BoardingPass>())
-        .collect::<Result<_, _>>()?;
-
+pub fn part2(passes: &[BoardingPass]) -> anyhow::Result<i32> {
     let list_ids: BTreeSet<i32> = passes
         .iter()
         .map(|pass| pass.find_seat().id())
@@ -144,6 +176,29 @@ pub fn part2(raw_input: &str) -> anyhow::Result<i32> {
     bail!("Id not found!");
 }
 
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u32 = 5;
+    const NAME: &'static str = "Binary Boarding";
+
+    type Input = Vec<BoardingPass>;
+    type Output1 = i32;
+    type Output2 = i32;
+
+    fn parse(input: &PuzzleInput) -> anyhow::Result<Self::Input> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> anyhow::Result<Self::Output1> {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> anyhow::Result<Self::Output2> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -176,6 +231,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_boarding_pass_errors() {
+        let error = parse_input(&PuzzleInput::new("FBFBBFFRLR\nFBFBXFFRLR\n")).unwrap_err();
+
+        assert_eq!(error.message, "Unexpected f/b character: X");
+        assert_eq!((error.line, error.columns), (2, 4..5));
+
+        let error = BoardingPass::from_str("FBFBBFFR").unwrap_err();
+
+        assert_eq!(error.columns, 8..8);
+    }
+
     #[test]
     fn test_find_seat() {
         let pass = BoardingPass::from_str("FBFBBFFRLR").unwrap();
@@ -186,4 +253,26 @@ mod tests {
     fn test_seat_id() {
         assert_eq!(Seat { row: 44, col: 5 }.id(), 357);
     }
+
+    #[test]
+    fn test_large_input() {
+        // The low `width` bits of `n`, written with `one` and `zero`.
+        let bits = |n: usize, width: usize, one: char, zero: char| -> String {
+            (0..width)
+                .rev()
+                .map(|bit| if n & (1 << bit) != 0 { one } else { zero })
+                .collect()
+        };
+
+        // Every seat but 500, each boarding pass repeated many times over.
+        let passes: String = (0..(128 * 8))
+            .filter(|&id| id != 500)
+            .map(|id| format!("{}{}\n", bits(id >> 3, 7, 'B', 'F'), bits(id, 3, 'R', 'L')))
+            .collect();
+
+        let passes = parse_input(&PuzzleInput::new(&passes.repeat(50))).unwrap();
+
+        assert_eq!(part1(&passes).unwrap(), 1023);
+        assert_eq!(part2(&passes).unwrap(), 500);
+    }
 }
@@ -1,6 +1,14 @@
 use std::collections::BTreeSet;
 
-fn sum_group_answers_any(group: &str) -> usize {
+use crate::input::PuzzleInput;
+use crate::parse::ParseError;
+use crate::solution::Solution;
+
+pub fn parse_input(input: &PuzzleInput) -> Result<Vec<String>, ParseError> {
+    Ok(input.blank_line_groups().map(String::from).collect())
+}
+
+pub fn sum_group_answers_any(group: &str) -> usize {
     group
         .chars()
         .filter(|c| !c.is_whitespace())
@@ -8,15 +16,15 @@ fn sum_group_answers_any(group: &str) -> usize {
         .len()
 }
 
-fn sum_all_groups_any(answers: &str) -> usize {
-    answers.split("\n\n").map(sum_group_answers_any).sum()
+pub fn sum_all_groups_any(groups: &[String]) -> usize {
+    groups.iter().map(|group| sum_group_answers_any(group)).sum()
 }
 
-pub fn part1(raw_input: &str) -> anyhow::Result<usize> {
-    Ok(sum_all_groups_any(raw_input))
+pub fn part1(groups: &[String]) -> anyhow::Result<usize> {
+    Ok(sum_all_groups_any(groups))
 }
 
-fn sum_group_answers_all(group: &str) -> usize {
+pub fn sum_group_answers_all(group: &str) -> usize {
     let all_answers = ('a'..='z').collect::<BTreeSet<char>>();
 
     group
@@ -31,12 +39,35 @@ fn sum_group_answers_all(group: &str) -> usize {
         .len()
 }
 
-fn sum_all_groups_all(answers: &str) -> usize {
-    answers.split("\n\n").map(sum_group_answers_all).sum()
+pub fn sum_all_groups_all(groups: &[String]) -> usize {
+    groups.iter().map(|group| sum_group_answers_all(group)).sum()
 }
 
-pub fn part2(raw_input: &str) -> anyhow::Result<usize> {
-    Ok(sum_all_groups_all(raw_input))
+pub fn part2(groups: &[String]) -> anyhow::Result<usize> {
+    Ok(sum_all_groups_all(groups))
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u32 = 6;
+    const NAME: &'static str = "Custom Customs";
+
+    type Input = Vec<String>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &PuzzleInput) -> anyhow::Result<Self::Input> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> anyhow::Result<Self::Output1> {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> anyhow::Result<Self::Output2> {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
@@ -58,25 +89,12 @@ mod tests {
 
     #[test]
     fn test_sum_all_groups_any() {
-        let answers = indoc! {"
-            abc
-
-            a
-            b
-            c
-
-            ab
-            ac
-
-            a
-            a
-            a
-            a
+        let answers = include_str!("../examples/day6/1.txt");
 
-            b
-        "};
-
-        assert_eq!(sum_all_groups_any(answers), 11);
+        assert_eq!(
+            sum_all_groups_any(&parse_input(&PuzzleInput::new(answers)).unwrap()),
+            11
+        );
     }
 
     #[test]
@@ -92,24 +110,27 @@ mod tests {
 
     #[test]
     fn test_sum_all_groups_all() {
-        let answers = indoc! {"
-            abc
+        let answers = include_str!("../examples/day6/1.txt");
 
-            a
-            b
-            c
+        assert_eq!(
+            sum_all_groups_all(&parse_input(&PuzzleInput::new(answers)).unwrap()),
+            6
+        );
 
-            ab
-            ac
+        let answers = answers.replace('\n', "\r\n");
 
-            a
-            a
-            a
-            a
+        assert_eq!(
+            sum_all_groups_all(&parse_input(&PuzzleInput::new(&answers)).unwrap()),
+            6
+        );
+    }
 
-            b
-        "};
+    #[test]
+    fn test_large_input() {
+        let group = "abcdefghijklmnopqrstuvwxyz\n".repeat(5) + "\n";
+        let groups = parse_input(&PuzzleInput::new(&group.repeat(2_000))).unwrap();
 
-        assert_eq!(sum_all_groups_all(answers), 6);
+        assert_eq!(part1(&groups).unwrap(), 52_000);
+        assert_eq!(part2(&groups).unwrap(), 52_000);
     }
 }
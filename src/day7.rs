@@ -1,17 +1,21 @@
 use std::collections::{BTreeMap, BTreeSet};
 
+use crate::input::PuzzleInput;
+use crate::parse::ParseError;
+use crate::solution::{Solution, Strategy};
+
 #[derive(Default, Debug, PartialEq)]
-struct Rules<'a> {
-    inner: BTreeMap<&'a str, BTreeMap<&'a str, i32>>,
+pub struct Rules {
+    inner: BTreeMap<String, BTreeMap<String, i32>>,
 }
 
-impl<'a> Rules<'a> {
-    fn new() -> Self {
+impl Rules {
+    pub fn new() -> Self {
         Self::default()
     }
 
-    fn add_rule(&mut self, s: &'a str) -> anyhow::Result<()> {
-        let ret_err = || anyhow::anyhow!("Could not parse rule: {}", s);
+    pub fn add_rule(&mut self, s: &str) -> Result<(), ParseError> {
+        let ret_err = || ParseError::new(s, s, format!("Could not parse rule: {}", s));
 
         let mut spaces = s.match_indices(' ');
 
@@ -25,7 +29,7 @@ impl<'a> Rules<'a> {
         let rest = &s[(i + 1)..];
 
         if rest == "no other bags." {
-            self.inner.insert(bag, BTreeMap::new());
+            self.inner.insert(bag.to_string(), BTreeMap::new());
             return Ok(());
         }
 
@@ -36,31 +40,39 @@ impl<'a> Rules<'a> {
 
             let (i, _) = spaces.next().ok_or_else(ret_err)?; // Quantity
 
-            let count = chunk[..i].parse::<i32>()?;
+            let count_str = &chunk[..i];
+            let count = count_str.parse::<i32>().map_err(|err| {
+                ParseError::new(s, count_str, format!("Invalid quantity: {}", err))
+            })?;
 
             spaces.next().ok_or_else(ret_err)?; // Adjective
             let (j, _) = spaces.next().ok_or_else(ret_err)?; // Color
 
             let bag = &chunk[(i + 1)..j];
 
-            bags.insert(bag, count);
+            bags.insert(bag.to_string(), count);
         }
 
-        self.inner.insert(bag, bags);
+        self.inner.insert(bag.to_string(), bags);
 
         Ok(())
     }
 
-    fn direct_contains(&self, s: &str) -> impl Iterator<Item = &str> {
+    /// The bags directly inside `bag`, with their quantities.
+    pub fn contents(&self, bag: &str) -> Option<&BTreeMap<String, i32>> {
+        self.inner.get(bag)
+    }
+
+    pub fn direct_contains(&self, s: &str) -> impl Iterator<Item = &str> {
         let s = String::from(s);
 
         self.inner
             .iter()
-            .filter(move |(_bag, bags)| bags.contains_key::<str>(&s))
-            .map(|(bag, _bags)| *bag)
+            .filter(move |(_bag, bags)| bags.contains_key(&s))
+            .map(|(bag, _bags)| bag.as_str())
     }
 
-    fn contains(&self, start_bag: &str) -> BTreeSet<&str> {
+    pub fn contains(&self, start_bag: &str) -> BTreeSet<&str> {
         use std::collections::VecDeque;
 
         let mut bags = BTreeSet::new();
@@ -81,12 +93,49 @@ impl<'a> Rules<'a> {
         bags
     }
 
-    fn num_contained_by(&self, top_bag: &str) -> anyhow::Result<i32> {
-        let mut total = 0;
+    /// Maps each bag to the bags that directly contain it, so the containers
+    /// of a bag can be found without scanning every rule.
+    pub fn reverse_index(&self) -> BTreeMap<&str, Vec<&str>> {
+        let mut index: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+        for (container, bags) in &self.inner {
+            for bag in bags.keys() {
+                index.entry(bag).or_default().push(container);
+            }
+        }
+
+        index
+    }
+
+    /// The same as [`Rules::contains`], using a [`Rules::reverse_index`].
+    pub fn contains_indexed<'a>(
+        &self,
+        index: &BTreeMap<&str, Vec<&'a str>>,
+        start_bag: &str,
+    ) -> BTreeSet<&'a str> {
+        let mut bags = BTreeSet::new();
+
+        let mut stack = vec![start_bag];
+
+        while let Some(current_bag) = stack.pop() {
+            for &bag in index.get(current_bag).into_iter().flatten() {
+                if bags.insert(bag) {
+                    stack.push(bag);
+                }
+            }
+        }
+
+        bags
+    }
+
+    pub fn num_contained_by(&self, top_bag: &str) -> anyhow::Result<i64> {
+        let overflow = || anyhow::anyhow!("Number of bags in {} overflows", top_bag);
+
+        let mut total: i64 = 0;
 
         let mut bag_stack = Vec::new();
 
-        bag_stack.push((top_bag, 1));
+        bag_stack.push((top_bag, 1_i64));
 
         while let Some((current_bag, count)) = bag_stack.pop() {
             let bags = self
@@ -95,48 +144,78 @@ impl<'a> Rules<'a> {
                 .ok_or_else(|| anyhow::anyhow!("Could not find entry for {}", current_bag))?;
 
             for (bag, c) in bags {
-                bag_stack.push((bag, *c * count))
+                let count = count.checked_mul(i64::from(*c)).ok_or_else(overflow)?;
+                bag_stack.push((bag.as_str(), count))
             }
 
-            total += count;
+            total = total.checked_add(count).ok_or_else(overflow)?;
         }
 
         Ok(total - 1)
     }
 }
 
-pub fn part1(raw_input: &str) -> anyhow::Result<usize> {
-    let rules = {
-        let mut rules = Rules::new();
+pub fn parse_input(input: &PuzzleInput) -> Result<Rules, ParseError> {
+    let mut rules = Rules::new();
 
-        for line in raw_input.lines() {
-            rules.add_rule(line)?;
-        }
+    for line in input.lines() {
+        let added = rules
+            .add_rule(line)
+            .map_err(|err| err.within(input.as_str(), line));
 
-        rules
-    };
+        input.recover(added)?;
+    }
+
+    Ok(rules)
+}
 
+pub fn part1(rules: &Rules) -> anyhow::Result<usize> {
     let num_bags = rules.contains("shiny gold").len();
 
     Ok(num_bags)
 }
 
-pub fn part2(raw_input: &str) -> anyhow::Result<i32> {
-    let rules = {
-        let mut rules = Rules::new();
-
-        for line in raw_input.lines() {
-            rules.add_rule(line)?;
-        }
+pub fn part1_reverse_index(rules: &Rules) -> anyhow::Result<usize> {
+    let index = rules.reverse_index();
+    let num_bags = rules.contains_indexed(&index, "shiny gold").len();
 
-        rules
-    };
+    Ok(num_bags)
+}
 
+pub fn part2(rules: &Rules) -> anyhow::Result<i64> {
     let num_bags = rules.num_contained_by("shiny gold")?;
 
     Ok(num_bags)
 }
 
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u32 = 7;
+    const NAME: &'static str = "Handy Haversacks";
+
+    type Input = Rules;
+    type Output1 = usize;
+    type Output2 = i64;
+
+    const STRATEGIES1: &'static [Strategy<Self::Input, Self::Output1>] = &[Strategy {
+        name: "reverse-index",
+        solve: part1_reverse_index,
+    }];
+
+    fn parse(input: &PuzzleInput) -> anyhow::Result<Self::Input> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> anyhow::Result<Self::Output1> {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> anyhow::Result<Self::Output2> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,32 +240,14 @@ mod tests {
         ($($k:expr => $v:expr),+ $(,)?) => {
             {
                 let mut temp = BTreeMap::new();
-                $(temp.insert($k, $v);)*
+                $(temp.insert($k.to_string(), $v);)*
                     temp
             }
         };
     }
 
-    fn sample_rules() -> Rules<'static> {
-        let input = [
-            "light red bags contain 1 bright white bag, 2 muted yellow bags.",
-            "dark orange bags contain 3 bright white bags, 4 muted yellow bags.",
-            "bright white bags contain 1 shiny gold bag.",
-            "muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.",
-            "shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.",
-            "dark olive bags contain 3 faded blue bags, 4 dotted black bags.",
-            "vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.",
-            "faded blue bags contain no other bags.",
-            "dotted black bags contain no other bags.",
-        ];
-
-        let mut rules = Rules::new();
-
-        for i in &input {
-            rules.add_rule(i).unwrap();
-        }
-
-        rules
+    fn sample_rules() -> Rules {
+        parse_input(&PuzzleInput::new(include_str!("../examples/day7/1.txt"))).unwrap()
     }
 
     #[test]
@@ -209,6 +270,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_rule_error() {
+        let input =
+            "faded blue bags contain no other bags.\nshiny gold bags contain x dark olive bags.\n";
+        let error = parse_input(&PuzzleInput::new(input)).unwrap_err();
+
+        assert_eq!(
+            error.message,
+            "Invalid quantity: invalid digit found in string"
+        );
+        assert_eq!((error.line, error.columns), (2, 24..25));
+
+        let error = parse_input(&PuzzleInput::new("shiny gold")).unwrap_err();
+
+        assert_eq!(error.message, "Could not parse rule: shiny gold");
+        assert_eq!(error.columns, 0..10);
+    }
+
+    #[test]
+    fn test_parse_rules_lenient() {
+        let input = include_str!("../examples/day7/1.txt").replace("2 shiny", "two shiny");
+        let input = PuzzleInput::lenient(&input);
+
+        let rules = parse_input(&input).unwrap();
+
+        assert!(rules.contents("muted yellow").is_none());
+        assert_eq!(input.take_rejected()[0].line, 4);
+    }
+
     #[test]
     fn test_direct_containment() {
         let rules = sample_rules();
@@ -229,6 +319,13 @@ mod tests {
             rules.contains("shiny gold"),
             b_tree_set! {"bright white", "muted yellow", "dark orange", "light red"}
         );
+
+        let index = rules.reverse_index();
+
+        assert_eq!(
+            rules.contains_indexed(&index, "shiny gold"),
+            rules.contains("shiny gold")
+        );
     }
 
     #[test]
@@ -236,5 +333,40 @@ mod tests {
         let rules = sample_rules();
 
         assert_eq!(rules.num_contained_by("shiny gold").unwrap(), 32);
+
+        let rules = parse_input(&PuzzleInput::new(include_str!("../examples/day7/2.txt"))).unwrap();
+
+        assert_eq!(rules.num_contained_by("shiny gold").unwrap(), 126);
+    }
+
+    /// Rules where each bag holds 1000 of the next, `depth` levels deep.
+    fn nested_rules(depth: usize) -> Rules {
+        let mut rules = Rules::new();
+
+        for i in 0..depth {
+            let rule = format!(
+                "level{} gold bags contain 1000 level{} gold bags.",
+                i,
+                i + 1
+            );
+            rules.add_rule(&rule).unwrap();
+        }
+
+        let rule = format!("level{} gold bags contain no other bags.", depth);
+        rules.add_rule(&rule).unwrap();
+
+        rules
+    }
+
+    #[test]
+    fn test_contained_by_large() {
+        let rules = nested_rules(4);
+        assert_eq!(
+            rules.num_contained_by("level0 gold").unwrap(),
+            1_001_001_001_000
+        );
+
+        let rules = nested_rules(7);
+        assert!(rules.num_contained_by("level0 gold").is_err());
     }
 }
@@ -4,37 +4,48 @@ use std::str::FromStr;
 
 use anyhow::bail;
 
+use crate::input::PuzzleInput;
+use crate::parse::ParseError;
+use crate::solution::{Solution, Strategy};
+
 #[derive(Clone, Copy, Debug, PartialEq)]
-enum Instruction {
+pub enum Instruction {
     NoOperation(i32),
     Accumulate(i32),
     Jump(i32),
 }
 
 impl FromStr for Instruction {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (instruction_str, count_str) = {
             let mut chunks = s.split_whitespace();
+            let end = &s[s.len()..];
 
             (
                 chunks
                     .next()
-                    .ok_or_else(|| anyhow::anyhow!("Could not parse instruction chunk"))?,
+                    .ok_or_else(|| ParseError::new(s, end, "Could not parse instruction chunk"))?,
                 chunks
                     .next()
-                    .ok_or_else(|| anyhow::anyhow!("Could not parse count chunk"))?,
+                    .ok_or_else(|| ParseError::new(s, end, "Could not parse count chunk"))?,
             )
         };
 
-        let value: i32 = count_str.parse()?;
+        let value: i32 = count_str
+            .parse()
+            .map_err(|err| ParseError::new(s, count_str, format!("Invalid count: {}", err)))?;
 
         let instruction = match instruction_str {
             "nop" => Self::NoOperation(value),
             "acc" => Self::Accumulate(value),
             "jmp" => Self::Jump(value),
-            _ => bail!("Invalid instruction: {}", instruction_str),
+            _ => {
+                let message = format!("Invalid instruction: {}", instruction_str);
+
+                return Err(ParseError::new(s, instruction_str, message));
+            }
         };
 
         Ok(instruction)
@@ -42,52 +53,67 @@ impl FromStr for Instruction {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-enum CompletionState {
+pub enum CompletionState {
     OutOfBounds,
     Loop,
     Finished,
 }
 
 #[derive(Clone, Debug, PartialEq)]
-struct Program {
+pub struct Program {
     instructions: Vec<Instruction>,
-    accumulator: i32,
+    accumulator: i64,
     counter: i32,
 }
 
 impl Program {
-    fn accumulator(&self) -> i32 {
+    pub fn accumulator(&self) -> i64 {
         self.accumulator
     }
 
-    fn counter(&self) -> i32 {
+    pub fn counter(&self) -> i32 {
         self.counter
     }
 
-    fn len(&self) -> usize {
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn len(&self) -> usize {
         self.instructions.len()
     }
 
-    fn step(&mut self) -> Option<i32> {
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Runs the instruction at the counter, returning where it was. Returns
+    /// `None` once the counter is outside the program, or if it jumps past
+    /// the range of an `i32`.
+    pub fn step(&mut self) -> Option<i32> {
         let ret = self.counter;
 
         let index: usize = self.counter.try_into().ok()?;
 
         let instruction = self.instructions.get(index)?;
 
-        self.counter += match instruction {
+        let offset = match instruction {
             Instruction::NoOperation(_) => 1,
             Instruction::Accumulate(count) => {
-                self.accumulator += count;
+                // An instruction adds at most `i32::MAX`, so this cannot
+                // overflow within any program that fits in memory.
+                self.accumulator += i64::from(*count);
                 1
             }
             Instruction::Jump(offset) => *offset,
         };
 
+        self.counter = self.counter.checked_add(offset)?;
+
         Some(ret)
     }
 
-    fn run(&mut self) -> CompletionState {
+    pub fn run(&mut self) -> CompletionState {
         use std::collections::HashSet;
 
         let mut executed_instructions = HashSet::new();
@@ -111,7 +137,7 @@ impl Program {
         }
     }
 
-    fn with_flipped_instruction(&self, index: usize) -> Option<Self> {
+    pub fn with_flipped_instruction(&self, index: usize) -> Option<Self> {
         let instruction = self.instructions.get(index)?;
 
         let new_instruction = match instruction {
@@ -125,6 +151,29 @@ impl Program {
 
         Some(new_instructions.into_iter().collect())
     }
+
+    /// Swaps the `jmp` or `nop` at `index` for the other, returning whether
+    /// there was one to swap.
+    pub fn flip_instruction(&mut self, index: usize) -> bool {
+        let instruction = match self.instructions.get_mut(index) {
+            Some(instruction) => instruction,
+            None => return false,
+        };
+
+        *instruction = match instruction {
+            Instruction::NoOperation(n) => Instruction::Jump(*n),
+            Instruction::Jump(n) => Instruction::NoOperation(*n),
+            _ => return false,
+        };
+
+        true
+    }
+
+    /// Puts the counter and accumulator back to where they started.
+    pub fn reset(&mut self) {
+        self.accumulator = 0;
+        self.counter = 0;
+    }
 }
 
 impl FromIterator<Instruction> for Program {
@@ -138,22 +187,21 @@ impl FromIterator<Instruction> for Program {
 }
 
 impl FromStr for Program {
-    type Err = anyhow::Error;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let program: Self = s
-            .lines()
-            .map(|line| Instruction::from_str(line))
-            .collect::<Result<Vec<_>, _>>()?
-            .into_iter()
-            .collect();
-
-        Ok(program)
+        parse_input(&PuzzleInput::new(s))
     }
 }
 
-pub fn part1(raw_input: &str) -> anyhow::Result<i32> {
-    let mut program: Program = raw_input.parse()?;
+pub fn parse_input(input: &PuzzleInput) -> Result<Program, ParseError> {
+    let program: Program = input.parse_lines::<Instruction>()?.into_iter().collect();
+
+    Ok(program)
+}
+
+pub fn part1(program: &Program) -> anyhow::Result<i64> {
+    let mut program = program.clone();
 
     if program.run() != CompletionState::Loop {
         bail!("Loop not found in program");
@@ -162,9 +210,7 @@ pub fn part1(raw_input: &str) -> anyhow::Result<i32> {
     Ok(program.accumulator())
 }
 
-pub fn part2(raw_input: &str) -> anyhow::Result<i32> {
-    let program: Program = raw_input.parse()?;
-
+pub fn part2(program: &Program) -> anyhow::Result<i64> {
     let modified_programs = (0..program.len())
         .filter_map(|instruction_index| program.with_flipped_instruction(instruction_index));
 
@@ -177,6 +223,56 @@ pub fn part2(raw_input: &str) -> anyhow::Result<i32> {
     bail!("No correct programs found");
 }
 
+/// Like [`part2`], but flips each instruction of one copy of the program in
+/// turn rather than copying the program for every flip.
+pub fn part2_in_place(program: &Program) -> anyhow::Result<i64> {
+    let mut program = program.clone();
+
+    for index in 0..program.len() {
+        if !program.flip_instruction(index) {
+            continue;
+        }
+
+        program.reset();
+
+        if program.run() == CompletionState::Finished {
+            return Ok(program.accumulator());
+        }
+
+        program.flip_instruction(index);
+    }
+
+    bail!("No correct programs found");
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u32 = 8;
+    const NAME: &'static str = "Handheld Halting";
+
+    type Input = Program;
+    type Output1 = i64;
+    type Output2 = i64;
+
+    const STRATEGIES2: &'static [Strategy<Self::Input, Self::Output2>] = &[Strategy {
+        name: "in-place",
+        solve: part2_in_place,
+    }];
+
+    fn parse(input: &PuzzleInput) -> anyhow::Result<Self::Input> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> anyhow::Result<Self::Output1> {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> anyhow::Result<Self::Output2> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -203,6 +299,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_program_error() {
+        let error = "nop +0\njpm +4\nacc +1\n".parse::<Program>().unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            indoc! {"
+                Invalid instruction: jpm
+                 --> line 2, column 1
+                  |
+                2 | jpm +4
+                  | ^^^"}
+        );
+
+        let error = Instruction::from_str("acc").unwrap_err();
+
+        assert_eq!(error.message, "Could not parse count chunk");
+    }
+
     #[test]
     fn test_collect_program() {
         assert_eq!(
@@ -220,17 +335,7 @@ mod tests {
 
     #[test]
     fn test_parse_program() {
-        let input = indoc! {"
-            nop +0
-            acc +1
-            jmp +4
-            acc +3
-            jmp -3
-            acc -99
-            acc +1
-            jmp -4
-            acc +6
-        "};
+        let input = include_str!("../examples/day8/1.txt");
 
         use Instruction::{Accumulate, Jump, NoOperation};
 
@@ -255,19 +360,7 @@ mod tests {
     }
 
     fn sample_program() -> Program {
-        let input = indoc! {"
-            nop +0
-            acc +1
-            jmp +4
-            acc +3
-            jmp -3
-            acc -99
-            acc +1
-            jmp -4
-            acc +6
-        "};
-
-        input.parse().unwrap()
+        include_str!("../examples/day8/1.txt").parse().unwrap()
     }
 
     #[test]
@@ -292,4 +385,33 @@ mod tests {
 
         assert_eq!(program.accumulator(), 5);
     }
+
+    #[test]
+    fn test_flip_instruction() {
+        let mut program = sample_program();
+
+        assert!(program.flip_instruction(7));
+        assert_eq!(program.run(), CompletionState::Finished);
+        assert_eq!(program.accumulator(), 8);
+
+        program.reset();
+        assert!(!program.flip_instruction(1));
+        assert!(!program.flip_instruction(9));
+        assert_eq!((program.accumulator(), program.counter()), (0, 0));
+    }
+
+    #[test]
+    fn test_large_values() {
+        let mut source = "acc +2147483647\n".repeat(1000);
+        source.push_str("jmp -1000\n");
+        let program: Program = source.parse().unwrap();
+
+        assert_eq!(part1(&program).unwrap(), 2_147_483_647_000);
+
+        let mut program: Program = "nop +0\njmp +2147483647\n".parse().unwrap();
+        program.counter = 1;
+
+        assert_eq!(program.step(), None);
+        assert_eq!(program.run(), CompletionState::OutOfBounds);
+    }
 }
@@ -0,0 +1,286 @@
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, bail, Context as _};
+
+pub const DEFAULT_URL: &str = "https://adventofcode.com/2020";
+
+/// Somewhere puzzle inputs can be downloaded from.
+pub trait Fetch: Send + Sync {
+    fn fetch(&self, day: u32) -> anyhow::Result<String>;
+}
+
+/// Downloads inputs over HTTP(S) from `{base_url}/day/{day}/input`, using
+/// the `curl` command so no TLS stack has to be built in.
+pub struct HttpFetcher {
+    base_url: String,
+    session: Option<String>,
+}
+
+impl HttpFetcher {
+    pub fn new(base_url: &str, session: Option<String>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session,
+        }
+    }
+
+    fn url(&self, day: u32) -> String {
+        format!("{}/day/{}/input", self.base_url, day)
+    }
+}
+
+impl Fetch for HttpFetcher {
+    fn fetch(&self, day: u32) -> anyhow::Result<String> {
+        // Headers are passed on stdin so the session token does not show up
+        // in the process list.
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--location"])
+            .args(["--user-agent", "advent-of-code-2020-rs"])
+            .args(["--header", "@-"])
+            .arg(self.url(day))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Could not run curl")?;
+
+        {
+            let mut stdin = child.stdin.take().expect("stdin is piped");
+
+            if let Some(session) = &self.session {
+                writeln!(stdin, "Cookie: session={}", session)?;
+            }
+        }
+
+        let output = child.wait_with_output()?;
+
+        if !output.status.success() {
+            bail!(
+                "Fetching {} failed: {}",
+                self.url(day),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        String::from_utf8(output.stdout).map_err(|_| anyhow!("Input for day {} is not UTF-8", day))
+    }
+}
+
+/// Finds puzzle inputs, preferring the local `dayN` files and falling back
+/// to a fetcher, whose downloads are saved for next time.
+pub struct InputProvider<'a> {
+    cache_dir: PathBuf,
+    fetcher: Option<&'a dyn Fetch>,
+}
+
+impl<'a> InputProvider<'a> {
+    pub fn new(cache_dir: PathBuf, fetcher: Option<&'a dyn Fetch>) -> Self {
+        Self { cache_dir, fetcher }
+    }
+
+    pub fn path(&self, day: u32) -> PathBuf {
+        self.cache_dir.join(format!("day{}", day))
+    }
+
+    /// The input for `day`. A blank file, like the placeholder `new-day`
+    /// writes, counts as missing when there is a fetcher.
+    pub fn input(&self, day: u32) -> anyhow::Result<String> {
+        let path = self.path(day);
+
+        let fetcher = match (fs::read_to_string(&path), self.fetcher) {
+            (Ok(input), Some(fetcher)) if input.trim().is_empty() => fetcher,
+            (Ok(input), _) => return Ok(input),
+            (Err(err), Some(fetcher)) if err.kind() == io::ErrorKind::NotFound => fetcher,
+            (Err(err), _) => return Err(err.into()),
+        };
+
+        let input = fetcher.fetch(day)?;
+
+        fs::create_dir_all(&self.cache_dir)
+            .and_then(|_| fs::write(&path, &input))
+            .with_context(|| format!("Could not save input to {}", path.display()))?;
+
+        Ok(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{BufRead as _, BufReader, Read as _};
+    use std::net::{SocketAddr, TcpListener};
+    use std::sync::Mutex;
+    use std::thread::{self, JoinHandle};
+
+    struct FakeFetcher {
+        calls: Mutex<Vec<u32>>,
+    }
+
+    impl Fetch for FakeFetcher {
+        fn fetch(&self, day: u32) -> anyhow::Result<String> {
+            self.calls.lock().unwrap().push(day);
+
+            match day {
+                1 => Ok(String::from("1721\n979\n")),
+                _ => bail!("No input for day {}", day),
+            }
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("aoc-fetch-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_provider_prefers_cache() {
+        let dir = temp_dir("cache");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("day1"), "cached\n").unwrap();
+
+        let fetcher = FakeFetcher {
+            calls: Mutex::new(Vec::new()),
+        };
+        let provider = InputProvider::new(dir.clone(), Some(&fetcher));
+
+        let input = provider.input(1);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(input.unwrap(), "cached\n");
+        assert!(fetcher.calls.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_provider_fetches_and_saves() {
+        let dir = temp_dir("save");
+
+        let fetcher = FakeFetcher {
+            calls: Mutex::new(Vec::new()),
+        };
+        let provider = InputProvider::new(dir.clone(), Some(&fetcher));
+
+        assert_eq!(provider.input(1).unwrap(), "1721\n979\n");
+        assert_eq!(fs::read_to_string(dir.join("day1")).unwrap(), "1721\n979\n");
+
+        // The second lookup is served from the saved file.
+        assert_eq!(provider.input(1).unwrap(), "1721\n979\n");
+        assert_eq!(*fetcher.calls.lock().unwrap(), vec![1]);
+
+        assert!(provider.input(2).is_err());
+        assert!(!dir.join("day2").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_provider_without_fetcher() {
+        let provider = InputProvider::new(temp_dir("none"), None);
+
+        assert!(provider.input(1).is_err());
+    }
+
+    fn has_curl() -> bool {
+        if Command::new("curl").arg("--version").output().is_err() {
+            eprintln!("curl is not installed, skipping");
+            return false;
+        }
+
+        true
+    }
+
+    /// Serves one request with `body`, returning the address to send it to
+    /// and a handle that yields the request's header lines.
+    fn stand_in_server(body: &'static str) -> (SocketAddr, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+
+                if line.trim_end().is_empty() {
+                    break;
+                }
+
+                request.push(line.trim_end().to_string());
+            }
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+
+            // Drain anything left so curl sees a clean close.
+            let _ = reader.read_to_end(&mut Vec::new());
+
+            request
+        });
+
+        (address, server)
+    }
+
+    #[test]
+    fn test_http_fetcher_against_stand_in_server() {
+        if !has_curl() {
+            return;
+        }
+
+        let (address, server) = stand_in_server("nop +0\nacc +1\n");
+
+        let fetcher = HttpFetcher::new(
+            &format!("http://{}/2020/", address),
+            Some(String::from("abc123")),
+        );
+
+        let input = fetcher.fetch(8).unwrap();
+        let request = server.join().unwrap();
+
+        assert_eq!(input, "nop +0\nacc +1\n");
+        assert_eq!(request[0], "GET /2020/day/8/input HTTP/1.1");
+        assert!(request.contains(&String::from("Cookie: session=abc123")));
+    }
+
+    #[test]
+    fn test_provider_fetches_over_placeholder() {
+        if !has_curl() {
+            return;
+        }
+
+        let dir = temp_dir("placeholder");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("day8"), " \n").unwrap();
+
+        let (address, server) = stand_in_server("nop +0\nacc +1\n");
+        let fetcher = HttpFetcher::new(&format!("http://{}/2020", address), None);
+        let provider = InputProvider::new(dir.clone(), Some(&fetcher));
+
+        let input = provider.input(8);
+        let saved = fs::read_to_string(dir.join("day8"));
+        let request = server.join().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(input.unwrap(), "nop +0\nacc +1\n");
+        assert_eq!(saved.unwrap(), "nop +0\nacc +1\n");
+        assert_eq!(request[0], "GET /2020/day/8/input HTTP/1.1");
+
+        // Without a fetcher the placeholder is read as it is.
+        let provider = InputProvider::new(dir.clone(), None);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("day8"), "").unwrap();
+
+        let input = provider.input(8);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(input.unwrap(), "");
+    }
+}
@@ -0,0 +1,190 @@
+//! A fuzz harness for the input parsers, run as an ordinary test.
+//!
+//! Every parser is fed generated puzzle inputs with random edits, and strings
+//! of random characters, and must return rather than panic. The run is
+//! reproducible from `AOC_FUZZ_SEED`, and `AOC_FUZZ_RUNS` sets how many
+//! inputs each parser gets:
+//!
+//! ```text
+//! AOC_FUZZ_SEED=1234 AOC_FUZZ_RUNS=100000 cargo test --release fuzz
+//! ```
+
+use std::collections::BTreeSet;
+use std::env;
+use std::panic::{self, AssertUnwindSafe};
+use std::str::FromStr;
+
+use crate::day2::Entry;
+use crate::day3::TreeMap;
+use crate::day4::{
+    BirthYear, EyeColor, HairColor, Height, LengthUnit, Passport, PassportId, SimplePassport,
+};
+use crate::day5::BoardingPass;
+use crate::day7::Rules;
+use crate::day8::{Instruction, Program};
+use crate::generate::{self, Rng};
+use crate::input::PuzzleInput;
+use crate::solution::SOLUTIONS;
+
+/// A parser to fuzz: its name, the inputs to give it and how to call it.
+type Target<'a> = (&'static str, &'a [String], fn(&str));
+
+const DEFAULT_SEED: u64 = 2020;
+const DEFAULT_RUNS: usize = 300;
+
+/// Characters that mean something to at least one parser, plus a few that
+/// mean nothing to any of them.
+const ALPHABET: &[char] = &[
+    '0', '1', '2', '9', '+', '-', ':', '#', ' ', '\n', '\r', '\t', ',', '.', 'a', 'c', 'f', 'z',
+    'B', 'F', 'L', 'R', 'x', 'é', '🎄', '\0',
+];
+
+fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// A string of up to `max_len` random characters.
+fn random_string(rng: &mut Rng, max_len: usize) -> String {
+    let len = rng.index(max_len + 1);
+
+    (0..len).map(|_| *rng.choose(ALPHABET)).collect()
+}
+
+/// `input` with a few random insertions, deletions, repeats and cuts.
+fn mutate(input: &str, rng: &mut Rng) -> String {
+    let mut chars: Vec<char> = input.chars().collect();
+
+    for _ in 0..=rng.index(4) {
+        let at = rng.index(chars.len() + 1);
+        let len = rng.index(chars.len() - at + 1).min(8);
+
+        match rng.index(5) {
+            0 => chars.insert(at, *rng.choose(ALPHABET)),
+            1 => {
+                chars.drain(at..(at + len));
+            }
+            2 => {
+                let repeated: Vec<char> = chars[at..(at + len)].to_vec();
+                chars.splice(at..at, repeated);
+            }
+            3 => chars.truncate(at),
+            _ => {
+                if let Some(c) = chars.get_mut(at) {
+                    *c = *rng.choose(ALPHABET);
+                }
+            }
+        }
+    }
+
+    chars.into_iter().collect()
+}
+
+/// Inputs for a parser: edited lines or records of a generated `day` input,
+/// edited whole inputs, and random strings.
+fn inputs(day: u32, runs: usize, rng: &mut Rng) -> Vec<String> {
+    let mut inputs = vec![String::new()];
+
+    while inputs.len() < runs {
+        let size = 1 + rng.index(4);
+        let input = generate::input(day, size, rng).unwrap_or_default();
+
+        inputs.push(mutate(&input, rng));
+
+        if let Some(line) = input.lines().next() {
+            inputs.push(mutate(line, rng));
+        }
+
+        inputs.push(random_string(rng, 40));
+    }
+
+    inputs
+}
+
+/// Runs `parse` on every input, collecting the ones it panicked on.
+fn panics<F>(inputs: &[String], parse: F) -> Vec<String>
+where
+    F: Fn(&str),
+{
+    inputs
+        .iter()
+        .filter(|input| panic::catch_unwind(AssertUnwindSafe(|| parse(input))).is_err())
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn fuzz_parsers() {
+    let seed = env_or("AOC_FUZZ_SEED", DEFAULT_SEED);
+    let runs = env_or("AOC_FUZZ_RUNS", DEFAULT_RUNS);
+
+    let mut rng = Rng::new(seed);
+
+    let day1 = inputs(1, runs, &mut rng);
+    let day2 = inputs(2, runs, &mut rng);
+    let day3 = inputs(3, runs, &mut rng);
+    let day4 = inputs(4, runs, &mut rng);
+    let day5 = inputs(5, runs, &mut rng);
+    let day6 = inputs(6, runs, &mut rng);
+    let day7 = inputs(7, runs, &mut rng);
+    let day8 = inputs(8, runs, &mut rng);
+
+    // Each field of a passport, taken from the passport inputs.
+    let fields: Vec<String> = day4
+        .iter()
+        .flat_map(|passport| passport.split_whitespace())
+        .map(|field| String::from(field.split(':').nth(1).unwrap_or(field)))
+        .collect();
+
+    let parsers: Vec<Target> = vec![
+        ("Entry", &day2, |s| drop(s.parse::<Entry>())),
+        ("TreeMap", &day3, |s| drop(s.parse::<TreeMap>())),
+        ("Passport", &day4, |s| drop(s.parse::<Passport>())),
+        ("SimplePassport", &day4, |s| {
+            drop(s.parse::<SimplePassport>())
+        }),
+        ("BirthYear", &fields, |s| drop(s.parse::<BirthYear>())),
+        ("Height", &fields, |s| drop(s.parse::<Height>())),
+        ("LengthUnit", &fields, |s| drop(s.parse::<LengthUnit>())),
+        ("HairColor", &fields, |s| drop(s.parse::<HairColor>())),
+        ("EyeColor", &fields, |s| drop(s.parse::<EyeColor>())),
+        ("PassportId", &fields, |s| drop(s.parse::<PassportId>())),
+        ("BoardingPass", &day5, |s| drop(s.parse::<BoardingPass>())),
+        ("Rules::add_rule", &day7, |s| drop(Rules::new().add_rule(s))),
+        ("Instruction", &day8, |s| drop(s.parse::<Instruction>())),
+        ("Program", &day8, |s| drop(s.parse::<Program>())),
+    ];
+
+    let mut failures = BTreeSet::new();
+
+    for (name, inputs, parse) in &parsers {
+        for input in panics(inputs, parse) {
+            failures.insert(format!("{} panicked on {:?}", name, input));
+        }
+    }
+
+    // Then each day's whole input, strictly and leniently.
+    let day_inputs = [&day1, &day2, &day3, &day4, &day5, &day6, &day7, &day8];
+
+    for (solution, inputs) in SOLUTIONS.iter().zip(day_inputs.iter()) {
+        let parse = |s: &str| {
+            drop(solution.parse(&PuzzleInput::new(s)));
+            drop(solution.parse(&PuzzleInput::lenient(s)));
+        };
+
+        for input in panics(inputs, parse) {
+            failures.insert(format!("Day {} panicked on {:?}", solution.day(), input));
+        }
+    }
+
+    let failures: Vec<_> = failures.into_iter().collect();
+
+    assert!(
+        failures.is_empty(),
+        "AOC_FUZZ_SEED={}:\n{}",
+        seed,
+        failures.join("\n")
+    );
+}
@@ -0,0 +1,466 @@
+//! Random puzzle inputs, for stress testing the parsers and solvers on
+//! inputs far larger than the real ones.
+//!
+//! Every input is valid: it parses, and both parts have exactly one answer.
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::ops::RangeInclusive;
+
+/// A small, seedable pseudo-random number generator (SplitMix64).
+///
+/// The same seed always gives the same numbers, so a generated input can be
+/// reproduced from its seed alone.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range`, which must not be empty.
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (start, end) = range.into_inner();
+        let width = (end - start) as u64 + 1;
+
+        // A width of 0 means the range covers every `i64`.
+        match width {
+            0 => self.next_u64() as i64,
+            width => start + (self.next_u64() % width) as i64,
+        }
+    }
+
+    /// An index below `len`, which must not be 0.
+    pub fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+
+    /// True with a probability of `numerator` in `denominator`.
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.next_u64() % denominator < numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+/// A random input for `day` with about `size` records, or `None` if there is
+/// no generator for that day.
+pub fn input(day: u32, size: usize, rng: &mut Rng) -> Option<String> {
+    let input = match day {
+        1 => expense_report(size, rng),
+        2 => password_database(size, rng),
+        3 => tree_map(size, rng),
+        4 => passport_batch(size, rng),
+        5 => boarding_passes(size, rng),
+        6 => customs_forms(size, rng),
+        7 => bag_rules(size, rng),
+        8 => handheld_program(size, rng),
+        _ => return None,
+    };
+
+    Some(input)
+}
+
+/// Expenses with exactly one pair and one triple adding to 2020. Everything
+/// else is over 2020, so cannot be part of either sum.
+fn expense_report(size: usize, rng: &mut Rng) -> String {
+    let planted = loop {
+        let pair = rng.range(1..=2019);
+        let first = rng.range(1..=2018);
+        let second = rng.range(1..=(2019 - first));
+        let planted = [pair, 2020 - pair, first, second, 2020 - first - second];
+
+        // Any other pair or triple of these adding to 2020 would give a
+        // second answer.
+        let pairs = (0..5)
+            .flat_map(|i| ((i + 1)..5).map(move |j| (i, j)))
+            .filter(|&(i, j)| planted[i] + planted[j] == 2020)
+            .count();
+
+        let triples = (0..5)
+            .flat_map(|i| ((i + 1)..5).flat_map(move |j| ((j + 1)..5).map(move |k| (i, j, k))))
+            .filter(|&(i, j, k)| planted[i] + planted[j] + planted[k] == 2020)
+            .count();
+
+        if pairs == 1 && triples == 1 {
+            break planted;
+        }
+    };
+
+    let mut values: Vec<i64> = planted.to_vec();
+    values.extend((planted.len()..size).map(|_| rng.range(2021..=999_999)));
+    rng.shuffle(&mut values);
+
+    values.iter().map(|value| format!("{}\n", value)).collect()
+}
+
+fn password_database(size: usize, rng: &mut Rng) -> String {
+    let mut input = String::new();
+
+    for _ in 0..size {
+        let length = rng.range(1..=20) as usize;
+        let character = (b'a' + rng.range(0..=5) as u8) as char;
+
+        let password: String = (0..length)
+            .map(|_| (b'a' + rng.range(0..=5) as u8) as char)
+            .collect();
+
+        let start = rng.range(1..=(length as i64));
+        let end = rng.range(start..=(length as i64));
+
+        writeln!(input, "{}-{} {}: {}", start, end, character, password).unwrap();
+    }
+
+    input
+}
+
+/// A tree map 31 squares wide. Large maps have fewer trees, so that the
+/// product of the trees hit on each slope still fits in a `usize`.
+fn tree_map(size: usize, rng: &mut Rng) -> String {
+    let sparseness = (size as u64 / 2000).max(5);
+
+    let mut input = String::new();
+
+    for _ in 0..size.max(1) {
+        for _ in 0..31 {
+            input.push(if rng.chance(1, sparseness) { '#' } else { '.' });
+        }
+
+        input.push('\n');
+    }
+
+    input
+}
+
+const EYE_COLORS: &[&str] = &["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
+/// A passport field with a value that is valid, or invalid if `valid` is
+/// false.
+fn passport_field(key: &str, valid: bool, rng: &mut Rng) -> String {
+    let year = |rng: &mut Rng, range: RangeInclusive<i64>| {
+        if valid {
+            rng.range(range).to_string()
+        } else {
+            (range.end() + rng.range(1..=50)).to_string()
+        }
+    };
+
+    let value = match key {
+        "byr" => year(rng, 1920..=2002),
+        "iyr" => year(rng, 2010..=2020),
+        "eyr" => year(rng, 2020..=2030),
+        "hgt" => match (valid, rng.chance(1, 2)) {
+            (true, true) => format!("{}cm", rng.range(150..=193)),
+            (true, false) => format!("{}in", rng.range(59..=76)),
+            (false, true) => format!("{}cm", rng.range(194..=250)),
+            (false, false) => rng.range(100..=200).to_string(),
+        },
+        "hcl" if valid => format!("#{:06x}", rng.range(0..=0xff_ffff)),
+        "hcl" => format!("{:06x}", rng.range(0..=0xff_ffff)),
+        "ecl" if valid => String::from(*rng.choose(EYE_COLORS)),
+        "ecl" => String::from("zzz"),
+        "pid" if valid => format!("{:09}", rng.range(0..=999_999_999)),
+        "pid" => format!("{:010}", rng.range(0..=9_999_999_999)),
+        _ => rng.range(100..=350).to_string(),
+    };
+
+    format!("{}:{}", key, value)
+}
+
+/// Passports that are valid, have an invalid value, or are missing a field.
+fn passport_batch(size: usize, rng: &mut Rng) -> String {
+    let required = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
+
+    let mut passports = Vec::new();
+
+    for _ in 0..size {
+        let mut keys: Vec<&str> = required.to_vec();
+
+        if rng.chance(1, 2) {
+            keys.push("cid");
+        }
+
+        if rng.chance(1, 4) {
+            keys.remove(rng.index(required.len()));
+        }
+
+        let invalid = if rng.chance(1, 3) {
+            Some(keys[rng.index(keys.len())])
+        } else {
+            None
+        };
+
+        rng.shuffle(&mut keys);
+
+        let mut passport = String::new();
+
+        for (i, key) in keys.iter().enumerate() {
+            if i > 0 {
+                passport.push(if rng.chance(1, 4) { '\n' } else { ' ' });
+            }
+
+            passport.push_str(&passport_field(key, Some(*key) != invalid, rng));
+        }
+
+        passports.push(passport);
+    }
+
+    passports.join("\n\n") + "\n"
+}
+
+fn boarding_pass(id: i64) -> String {
+    let row = (0..7).map(|bit| if id & (1 << (9 - bit)) == 0 { 'F' } else { 'B' });
+    let col = (7..10).map(|bit| if id & (1 << (9 - bit)) == 0 { 'L' } else { 'R' });
+
+    row.chain(col).collect()
+}
+
+/// Boarding passes for a run of seats with one missing in the middle. Sizes
+/// beyond the number of seats repeat passes.
+fn boarding_passes(size: usize, rng: &mut Rng) -> String {
+    // The seats either side of the run must stay empty, so the missing seat
+    // is the only one with both neighbours taken.
+    let seats = (size as i64 + 1).clamp(3, 1020);
+    let first = rng.range(2..=(1022 - seats));
+    let missing = rng.range((first + 1)..=(first + seats - 2));
+
+    let mut ids: Vec<i64> = (first..(first + seats))
+        .filter(|id| *id != missing)
+        .collect();
+
+    while ids.len() < size {
+        ids.push(ids[rng.index(ids.len())]);
+    }
+
+    rng.shuffle(&mut ids);
+
+    ids.iter()
+        .map(|id| format!("{}\n", boarding_pass(*id)))
+        .collect()
+}
+
+fn customs_forms(size: usize, rng: &mut Rng) -> String {
+    let mut groups = Vec::new();
+
+    for _ in 0..size {
+        let people = rng.range(1..=5);
+        let mut group = String::new();
+
+        for _ in 0..people {
+            let mut questions: Vec<char> = ('a'..='z').filter(|_| rng.chance(1, 3)).collect();
+
+            if questions.is_empty() {
+                questions.push((b'a' + rng.range(0..=25) as u8) as char);
+            }
+
+            rng.shuffle(&mut questions);
+
+            group.extend(questions);
+            group.push('\n');
+        }
+
+        groups.push(group);
+    }
+
+    groups.join("\n")
+}
+
+const ADJECTIVES: &[&str] = &[
+    "bright", "clear", "dark", "dim", "dotted", "drab", "dull", "faded", "light", "mirrored",
+    "muted", "pale", "plaid", "posh", "shiny", "striped", "vibrant", "wavy",
+];
+
+const COLORS: &[&str] = &[
+    "aqua", "beige", "black", "blue", "bronze", "brown", "coral", "crimson", "cyan", "fuchsia",
+    "gold", "gray", "green", "indigo", "lavender", "lime", "magenta", "maroon", "olive", "orange",
+    "plum", "purple", "red", "salmon", "silver", "tan", "teal", "tomato", "violet", "white",
+    "yellow",
+];
+
+/// Rules where bags only hold bags later in a random order, so there are no
+/// cycles. Shiny gold is kept near the end, so the number of bags inside it
+/// stays well within range however large the input.
+fn bag_rules(size: usize, rng: &mut Rng) -> String {
+    let mut names = BTreeSet::new();
+    names.insert(String::from("shiny gold"));
+
+    while names.len() < size.max(1) {
+        let mut name = format!("{} {}", rng.choose(ADJECTIVES), rng.choose(COLORS));
+
+        // Number the colour once the plain names run out.
+        if names.contains(&name) {
+            write!(name, "{}", rng.range(2..=(size as i64))).unwrap();
+        }
+
+        names.insert(name);
+    }
+
+    names.remove("shiny gold");
+
+    let mut order: Vec<String> = names.into_iter().collect();
+    rng.shuffle(&mut order);
+
+    let gold = order.len() - order.len().min(rng.range(0..=10) as usize);
+    order.insert(gold, String::from("shiny gold"));
+
+    let mut rules: Vec<String> = order
+        .iter()
+        .enumerate()
+        .map(|(i, bag)| {
+            let later = order.len() - i - 1;
+            let count = (rng.range(0..=4) as usize).min(later);
+
+            let mut contents = BTreeSet::new();
+            while contents.len() < count {
+                contents.insert(i + 1 + rng.index(later));
+            }
+
+            let contents: Vec<String> = contents
+                .iter()
+                .map(|&j| match rng.range(1..=5) {
+                    1 => format!("1 {} bag", order[j]),
+                    n => format!("{} {} bags", n, order[j]),
+                })
+                .collect();
+
+            if contents.is_empty() {
+                format!("{} bags contain no other bags.", bag)
+            } else {
+                format!("{} bags contain {}.", bag, contents.join(", "))
+            }
+        })
+        .collect();
+
+    rng.shuffle(&mut rules);
+
+    rules.iter().map(|rule| format!("{}\n", rule)).collect()
+}
+
+/// A program that loops, and terminates if and only if one `jmp` is changed
+/// to a `nop`.
+///
+/// Execution runs forwards from the start to a `jmp` back to the start,
+/// which is the instruction to fix. Every jump and `nop` before it stays
+/// before it, so no other change gets past it, and nothing after it is ever
+/// reached in the original program.
+fn handheld_program(size: usize, rng: &mut Rng) -> String {
+    let len = size.max(2) as i64;
+    let fix = rng.range(((len - 1) / 2).max(1)..=(len - 1));
+
+    let mut program = Vec::new();
+    let mut next = 0;
+
+    for i in 0..fix {
+        let executed = i == next;
+
+        let instruction = match rng.range(0..=2) {
+            0 => format!("acc {:+}", rng.range(-99..=99)),
+            1 => format!("nop {:+}", rng.range(0..=fix) - i),
+            // Executed jumps only go forwards, so execution reaches the fix.
+            _ if executed => format!("jmp {:+}", rng.range((i + 1)..=fix) - i),
+            _ => format!("jmp {:+}", rng.range(0..=fix) - i),
+        };
+
+        if executed {
+            next = match instruction.strip_prefix("jmp ") {
+                Some(offset) => i + offset.parse::<i64>().unwrap(),
+                None => i + 1,
+            };
+        }
+
+        program.push(instruction);
+    }
+
+    program.push(format!("jmp {:+}", -fix));
+
+    for _ in (fix + 1)..len {
+        program.push(match rng.range(0..=1) {
+            0 => format!("acc {:+}", rng.range(-99..=99)),
+            _ => format!("nop {:+}", rng.range(-99..=99)),
+        });
+    }
+
+    program.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::day8::{CompletionState, Program};
+    use crate::input::PuzzleInput;
+    use crate::solution;
+
+    #[test]
+    fn test_rng_is_seeded() {
+        let numbers = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..5).map(|_| rng.range(-10..=10)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(numbers(7), numbers(7));
+        assert_ne!(numbers(7), numbers(8));
+        assert!(numbers(7).iter().all(|n| (-10..=10).contains(n)));
+    }
+
+    #[test]
+    fn test_inputs_solve() {
+        for day in 1..=8 {
+            for &size in &[1, 10, 500] {
+                let mut rng = Rng::new(u64::from(day) * 1000 + size as u64);
+
+                let input = input(day, size, &mut rng).unwrap();
+                let solution = solution::find(day).unwrap();
+
+                let parsed = solution
+                    .parse(&PuzzleInput::new(&input))
+                    .unwrap_or_else(|err| panic!("day {} size {}: {}\n{}", day, size, err, input));
+
+                for part in 1..=2 {
+                    if let Err(err) = solution.solve(part, &parsed) {
+                        panic!(
+                            "day {} size {} part {}: {}\n{}",
+                            day, size, part, err, input
+                        );
+                    }
+                }
+            }
+        }
+
+        assert!(input(9, 10, &mut Rng::new(0)).is_none());
+    }
+
+    #[test]
+    fn test_program_has_one_fix() {
+        for seed in 0..20 {
+            let program: Program = handheld_program(30, &mut Rng::new(seed)).parse().unwrap();
+
+            let fixes = (0..program.len())
+                .filter_map(|index| program.with_flipped_instruction(index))
+                .filter(|program| program.clone().run() == CompletionState::Finished)
+                .count();
+
+            assert_eq!(fixes, 1, "seed {}", seed);
+        }
+    }
+}
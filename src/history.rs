@@ -0,0 +1,433 @@
+//! A record of past runs, kept as one JSON object per line, to compare new
+//! runs against.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write as _};
+use std::path::Path;
+use std::time::Duration;
+
+use advent_of_code_2020_rs::solution::DEFAULT_STRATEGY;
+use anyhow::{anyhow, bail, Context as _};
+
+use crate::json::Value;
+use crate::runner::DayReport;
+
+pub const HISTORY_FILE: &str = ".aoc-history.jsonl";
+
+/// Percentage a part may slow down by before it is flagged.
+pub const THRESHOLD: f64 = 10.0;
+
+/// What to compare a run with.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Baseline {
+    /// The most recent recording of each part.
+    Previous,
+    /// The most recent recording of each part in a run with this label.
+    Label(String),
+}
+
+/// Identifies a part across runs: its day, the example it ran on, if any,
+/// the part number and the strategy, since timing one strategy against
+/// another says nothing about a regression.
+type Key = (u32, Option<String>, u32, String);
+
+/// One part of a recorded run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartRecord {
+    pub day: u32,
+    pub example: Option<String>,
+    pub part: u32,
+    pub strategy: String,
+    /// The answer as text, or `None` if the part failed.
+    pub answer: Option<String>,
+    /// Median time taken, if the part succeeded.
+    pub median: Option<Duration>,
+}
+
+impl PartRecord {
+    fn key(&self) -> Key {
+        (
+            self.day,
+            self.example.clone(),
+            self.part,
+            self.strategy.clone(),
+        )
+    }
+
+    fn to_json(&self) -> Value {
+        let median = self.median.map_or(Value::Null, |median| {
+            Value::Integer(median.as_nanos() as i128)
+        });
+
+        Value::Object(vec![
+            (String::from("day"), self.day.into()),
+            (String::from("example"), self.example.as_deref().into()),
+            (String::from("part"), self.part.into()),
+            (String::from("strategy"), self.strategy.as_str().into()),
+            (String::from("answer"), self.answer.as_deref().into()),
+            (String::from("median_ns"), median),
+        ])
+    }
+
+    fn from_json(value: &Value) -> anyhow::Result<Self> {
+        let integer = |key| {
+            value
+                .get(key)
+                .and_then(Value::as_integer)
+                .ok_or_else(|| anyhow!("Missing {}", key))
+        };
+        let string = |key| value.get(key).and_then(Value::as_str).map(String::from);
+
+        Ok(Self {
+            day: integer("day")? as u32,
+            example: string("example"),
+            part: integer("part")? as u32,
+            strategy: string("strategy").ok_or_else(|| anyhow!("Missing strategy"))?,
+            answer: string("answer"),
+            median: integer("median_ns")
+                .ok()
+                .map(|nanos| Duration::from_nanos(nanos as u64)),
+        })
+    }
+}
+
+/// The parts of one run of the runner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    /// When the run happened, in seconds since the Unix epoch.
+    pub time: u64,
+    pub label: Option<String>,
+    pub parts: Vec<PartRecord>,
+}
+
+impl Run {
+    pub fn new(time: u64, label: Option<String>) -> Self {
+        Self {
+            time,
+            label,
+            parts: Vec::new(),
+        }
+    }
+
+    /// Adds the parts of `report` that ran.
+    pub fn add(&mut self, report: &DayReport) {
+        self.parts
+            .extend(report.parts.iter().map(|part| PartRecord {
+                day: report.day,
+                example: report.example.clone(),
+                part: part.part,
+                strategy: String::from(part.strategy),
+                answer: part.result.as_ref().ok().map(ToString::to_string),
+                median: part.time.map(|time| time.median),
+            }));
+    }
+
+    fn to_json(&self) -> Value {
+        Value::Object(vec![
+            (String::from("time"), self.time.into()),
+            (String::from("label"), self.label.as_deref().into()),
+            (
+                String::from("parts"),
+                Value::Array(self.parts.iter().map(PartRecord::to_json).collect()),
+            ),
+        ])
+    }
+
+    fn from_json(value: &Value) -> anyhow::Result<Self> {
+        let parts = value
+            .get("parts")
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("Missing parts"))?;
+
+        Ok(Self {
+            time: value.get("time").and_then(Value::as_integer).unwrap_or(0) as u64,
+            label: value.get("label").and_then(Value::as_str).map(String::from),
+            parts: parts
+                .iter()
+                .map(PartRecord::from_json)
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+/// Reads every run recorded in `path`, oldest first. A missing file holds no
+/// runs.
+pub fn load(path: &Path) -> anyhow::Result<Vec<Run>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("Reading {}", path.display())),
+    };
+
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            line.parse()
+                .and_then(|value| Run::from_json(&value))
+                .with_context(|| format!("{} line {}", path.display(), i + 1))
+        })
+        .collect()
+}
+
+/// Adds `run` to the end of `path`.
+pub fn append(path: &Path, run: &Run) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Opening {}", path.display()))?;
+
+    writeln!(file, "{}", run.to_json()).with_context(|| format!("Writing {}", path.display()))
+}
+
+/// The recording of each part to compare with, out of `runs`.
+fn baseline_parts<'a>(
+    runs: &'a [Run],
+    baseline: &Baseline,
+) -> anyhow::Result<BTreeMap<Key, &'a PartRecord>> {
+    let runs: Vec<_> = match baseline {
+        Baseline::Previous => runs.iter().collect(),
+        Baseline::Label(label) => {
+            let runs: Vec<_> = runs
+                .iter()
+                .filter(|run| run.label.as_ref() == Some(label))
+                .collect();
+
+            if runs.is_empty() {
+                bail!("No recorded run is labelled {}", label);
+            }
+
+            runs
+        }
+    };
+
+    Ok(runs
+        .into_iter()
+        .flat_map(|run| &run.parts)
+        .map(|part| (part.key(), part))
+        .collect())
+}
+
+/// How one part of a run differs from its baseline.
+#[derive(Debug, PartialEq)]
+pub struct Delta<'a> {
+    pub before: Option<&'a PartRecord>,
+    pub after: &'a PartRecord,
+    /// Whether the part got slower by more than the threshold.
+    pub slower: bool,
+}
+
+impl Delta<'_> {
+    pub fn answer_changed(&self) -> bool {
+        self.before
+            .is_some_and(|before| before.answer != self.after.answer)
+    }
+
+    pub fn flagged(&self) -> bool {
+        self.slower || self.answer_changed()
+    }
+}
+
+fn describe_answer(answer: Option<&str>) -> String {
+    match answer {
+        Some(answer) if answer.contains('\n') => String::from("a multi-line answer"),
+        Some(answer) => String::from(answer),
+        None => String::from("an error"),
+    }
+}
+
+impl fmt::Display for Delta<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let after = self.after;
+
+        write!(f, "Day {} part {}", after.day, after.part)?;
+
+        let mut notes = Vec::new();
+
+        if let Some(example) = &after.example {
+            notes.push(format!("example {}", example));
+        }
+
+        if after.strategy != DEFAULT_STRATEGY {
+            notes.push(after.strategy.clone());
+        }
+
+        if !notes.is_empty() {
+            write!(f, " ({})", notes.join(", "))?;
+        }
+
+        f.write_str(":")?;
+
+        let before = match self.before {
+            Some(before) => before,
+            None => return f.write_str(" new"),
+        };
+
+        match (before.median, after.median) {
+            (Some(old), Some(new)) => {
+                let change = (new.as_secs_f64() / old.as_secs_f64() - 1.0) * 100.0;
+
+                write!(f, " {:.2?} -> {:.2?}", old, new)?;
+
+                if change.is_finite() {
+                    write!(f, " ({:+.1}%)", change)?;
+                }
+            }
+            (_, Some(new)) => write!(f, " {:.2?}", new)?,
+            (_, None) => f.write_str(" failed")?,
+        }
+
+        if self.slower {
+            f.write_str(", slower")?;
+        }
+
+        if self.answer_changed() {
+            write!(
+                f,
+                ", answer changed from {} to {}",
+                describe_answer(before.answer.as_deref()),
+                describe_answer(after.answer.as_deref())
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares each part of `run` with its baseline out of the recorded `runs`,
+/// flagging parts more than `threshold` percent slower.
+pub fn compare<'a>(
+    runs: &'a [Run],
+    baseline: &Baseline,
+    run: &'a Run,
+    threshold: f64,
+) -> anyhow::Result<Vec<Delta<'a>>> {
+    let parts = baseline_parts(runs, baseline)?;
+
+    Ok(run
+        .parts
+        .iter()
+        .map(|after| {
+            let before = parts.get(&after.key()).copied();
+
+            let slower = match (before.and_then(|before| before.median), after.median) {
+                (Some(old), Some(new)) => {
+                    new.as_secs_f64() > old.as_secs_f64() * (1.0 + threshold / 100.0)
+                }
+                _ => false,
+            };
+
+            Delta {
+                before,
+                after,
+                slower,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(day: u32, answer: Option<&str>, millis: u64) -> PartRecord {
+        PartRecord {
+            day,
+            example: None,
+            part: 1,
+            strategy: String::from("default"),
+            answer: answer.map(String::from),
+            median: Some(Duration::from_millis(millis)),
+        }
+    }
+
+    fn run(label: Option<&str>, parts: Vec<PartRecord>) -> Run {
+        Run {
+            time: 1_607_000_000,
+            label: label.map(String::from),
+            parts,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut failed = part(8, None, 0);
+        failed.example = Some(String::from("1"));
+        failed.median = None;
+
+        let original = run(
+            Some("baseline"),
+            vec![
+                part(1, Some("514579"), 3),
+                part(3, Some("#.\n.#"), 1),
+                failed,
+            ],
+        );
+
+        let value = original.to_json().to_string().parse().unwrap();
+
+        assert_eq!(Run::from_json(&value).unwrap(), original);
+    }
+
+    #[test]
+    fn test_compare() {
+        let runs = vec![
+            run(
+                Some("baseline"),
+                vec![part(1, Some("5"), 10), part(2, Some("7"), 10)],
+            ),
+            run(None, vec![part(1, Some("5"), 20)]),
+        ];
+
+        let current = run(
+            None,
+            vec![
+                part(1, Some("5"), 21),
+                part(2, Some("8"), 10),
+                part(3, Some("1"), 5),
+            ],
+        );
+
+        let deltas = compare(&runs, &Baseline::Previous, &current, THRESHOLD).unwrap();
+        let lines: Vec<_> = deltas.iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "Day 1 part 1: 20.00ms -> 21.00ms (+5.0%)",
+                "Day 2 part 1: 10.00ms -> 10.00ms (+0.0%), answer changed from 7 to 8",
+                "Day 3 part 1: new",
+            ]
+        );
+        assert_eq!(
+            deltas.iter().map(Delta::flagged).collect::<Vec<_>>(),
+            vec![false, true, false]
+        );
+
+        let baseline = Baseline::Label(String::from("baseline"));
+        let deltas = compare(&runs, &baseline, &current, THRESHOLD).unwrap();
+
+        assert!(deltas[0].slower);
+        assert_eq!(
+            deltas[0].to_string(),
+            "Day 1 part 1: 10.00ms -> 21.00ms (+110.0%), slower"
+        );
+
+        // A part solved with another strategy has nothing to compare with.
+        let mut other = part(1, Some("5"), 40);
+        other.strategy = String::from("hash-set");
+
+        let current = run(None, vec![other]);
+        let deltas = compare(&runs, &Baseline::Previous, &current, THRESHOLD).unwrap();
+
+        assert_eq!(deltas[0].before, None);
+        assert!(!deltas[0].flagged());
+        assert_eq!(deltas[0].to_string(), "Day 1 part 1 (hash-set): new");
+
+        let missing = Baseline::Label(String::from("missing"));
+        assert!(compare(&runs, &missing, &current, THRESHOLD).is_err());
+    }
+}
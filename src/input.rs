@@ -0,0 +1,176 @@
+//! Reading puzzle input the same way for every day.
+
+use std::cell::RefCell;
+use std::mem;
+use std::str::FromStr;
+
+use crate::parse::ParseError;
+
+/// Puzzle input with its line endings and whitespace normalised.
+///
+/// Every line ends in `\n` rather than `\r\n`, has no trailing whitespace,
+/// and there are no blank lines at the end. Lines keep their numbers, so
+/// errors still point at the right place in the original file.
+///
+/// A lenient input lets parsers skip records they cannot read rather than
+/// fail, keeping the errors for [`PuzzleInput::take_rejected`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PuzzleInput {
+    text: String,
+    lenient: bool,
+    rejected: RefCell<Vec<ParseError>>,
+}
+
+impl PuzzleInput {
+    pub fn new(raw_input: &str) -> Self {
+        let mut text = String::with_capacity(raw_input.len());
+
+        for line in raw_input.lines() {
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+
+        let len = text.trim_end_matches('\n').len();
+        text.truncate(len);
+
+        if !text.is_empty() {
+            text.push('\n');
+        }
+
+        Self {
+            text,
+            lenient: false,
+            rejected: RefCell::default(),
+        }
+    }
+
+    pub fn lenient(raw_input: &str) -> Self {
+        Self {
+            lenient: true,
+            ..Self::new(raw_input)
+        }
+    }
+
+    /// Passes `result` through, unless the input is lenient and it failed,
+    /// in which case the error is set aside and the record skipped.
+    pub fn recover<T>(&self, result: Result<T, ParseError>) -> Result<Option<T>, ParseError> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if self.lenient => {
+                self.rejected.borrow_mut().push(err);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// The errors for every record skipped so far, in input order.
+    pub fn take_rejected(&self) -> Vec<ParseError> {
+        mem::take(&mut *self.rejected.borrow_mut())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.text.lines()
+    }
+
+    /// Runs of lines separated by one or more blank lines, without their
+    /// final newline.
+    pub fn blank_line_groups(&self) -> impl Iterator<Item = &str> {
+        self.text
+            .split("\n\n")
+            .map(|group| group.trim_matches('\n'))
+            .filter(|group| !group.is_empty())
+    }
+
+    /// Parses every line as a `T`.
+    pub fn parse_lines<T>(&self) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr<Err = ParseError>,
+    {
+        self.parse_lines_with(T::from_str)
+    }
+
+    /// Parses every line with `parse`, moving its errors to the line's place
+    /// in the whole input. Lines that fail are skipped if the input is
+    /// lenient.
+    pub fn parse_lines_with<T, F>(&self, parse: F) -> Result<Vec<T>, ParseError>
+    where
+        F: Fn(&str) -> Result<T, ParseError>,
+    {
+        self.lines()
+            .map(|line| self.recover(parse(line).map_err(|err| err.within(&self.text, line))))
+            .filter_map(Result::transpose)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalise() {
+        let input = PuzzleInput::new("1-3 a: abcde  \r\n\r\n2-9 c: ccc\t\r\n\r\n\r\n");
+
+        assert_eq!(input.as_str(), "1-3 a: abcde\n\n2-9 c: ccc\n");
+
+        assert_eq!(PuzzleInput::new("nop +0").as_str(), "nop +0\n");
+        assert_eq!(PuzzleInput::new("\n \n").as_str(), "");
+    }
+
+    #[test]
+    fn test_blank_line_groups() {
+        let input = PuzzleInput::new("abc\r\n\r\na\r\nb\r\n\r\n\r\nac\r\n");
+
+        assert_eq!(
+            input.blank_line_groups().collect::<Vec<_>>(),
+            vec!["abc", "a\nb", "ac"]
+        );
+    }
+
+    #[test]
+    fn test_parse_lines_with() {
+        let parse = |line: &str| {
+            line.parse::<i32>()
+                .map_err(|_| ParseError::new(line, line, "Invalid number"))
+        };
+
+        let input = PuzzleInput::new("12 \r\n34\r\n");
+        assert_eq!(input.parse_lines_with(parse).unwrap(), vec![12, 34]);
+
+        let input = PuzzleInput::new("12\nx4\n");
+        let error = input.parse_lines_with(parse).unwrap_err();
+        assert_eq!((error.line, error.text.as_str()), (2, "x4"));
+    }
+
+    #[test]
+    fn test_lenient() {
+        let parse = |line: &str| {
+            line.parse::<i32>()
+                .map_err(|_| ParseError::new(line, line, "Invalid number"))
+        };
+
+        let input = PuzzleInput::lenient("12\nx4\n56\n\n7y\n");
+        assert_eq!(input.parse_lines_with(parse).unwrap(), vec![12, 56]);
+
+        let rejected: Vec<_> = input
+            .take_rejected()
+            .into_iter()
+            .map(|err| (err.line, err.text))
+            .collect();
+
+        assert_eq!(
+            rejected,
+            vec![
+                (2, String::from("x4")),
+                (4, String::new()),
+                (5, String::from("7y"))
+            ]
+        );
+        assert!(input.take_rejected().is_empty());
+    }
+}
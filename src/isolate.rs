@@ -0,0 +1,217 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Once};
+use std::thread;
+use std::time::Duration;
+
+const THREAD_NAME: &str = "solver";
+
+thread_local! {
+    /// Where the last panic on this thread happened.
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Replaces the default panic output for solver threads, whose panics are
+/// reported as part failures instead. Other threads keep the default hook.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        let default_hook = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            if thread::current().name() == Some(THREAD_NAME) {
+                let location = info.location().map(ToString::to_string);
+                PANIC_LOCATION.with(|cell| *cell.borrow_mut() = location);
+            } else {
+                default_hook(info);
+            }
+        }));
+    });
+}
+
+/// Why an isolated closure did not produce a value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SolveError {
+    /// The closure returned an error.
+    Error(String),
+    /// The closure panicked.
+    Panic(String),
+    /// The closure was still running when the timeout expired.
+    Timeout(Duration),
+}
+
+impl SolveError {
+    pub fn status(&self) -> &'static str {
+        match self {
+            Self::Error(_) => "error",
+            Self::Panic(_) => "panic",
+            Self::Timeout(_) => "timeout",
+        }
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(err) => f.write_str(err),
+            Self::Panic(message) => write!(f, "Panicked: {}", message),
+            Self::Timeout(timeout) => write!(f, "Timed out after {:.2?}", timeout),
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    let message = if let Some(s) = payload.downcast_ref::<&str>() {
+        String::from(*s)
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        String::from("unknown panic payload")
+    };
+
+    match PANIC_LOCATION.with(|cell| cell.borrow_mut().take()) {
+        Some(location) => format!("{} at {}", message, location),
+        None => message,
+    }
+}
+
+/// Counts the threads run for something, such as one day, that have not
+/// finished yet. Clones share the count.
+#[derive(Clone, Debug, Default)]
+pub struct Workers(Arc<AtomicUsize>);
+
+impl Workers {
+    /// How many threads are still running. When nothing is being run, these
+    /// are the threads that timed out.
+    pub fn running(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    fn start(&self) -> Running {
+        self.0.fetch_add(1, Ordering::SeqCst);
+
+        Running(self.clone())
+    }
+}
+
+/// Counts a thread as running until it is dropped, when the thread ends.
+struct Running(Workers);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        (self.0).0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Runs `f` on its own thread, counted in `workers`, turning panics into
+/// errors and giving up on it once `timeout` has passed.
+///
+/// A thread that times out cannot be stopped, so it is left running in the
+/// background until it finishes or the process exits, and stays counted in
+/// `workers` until then.
+pub fn run<T, F>(timeout: Option<Duration>, workers: &Workers, f: F) -> Result<T, SolveError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> anyhow::Result<T> + Send + 'static,
+{
+    install_panic_hook();
+
+    let (sender, receiver) = mpsc::channel();
+    let running = workers.start();
+
+    thread::Builder::new()
+        .name(String::from(THREAD_NAME))
+        .spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(f)).map_err(panic_message);
+
+            // Stop counting the thread before handing over the result, so
+            // that it is never counted once `run` has returned it.
+            drop(running);
+
+            // The receiver is gone if the run already timed out.
+            let _ = sender.send(result);
+        })
+        .map_err(|err| SolveError::Error(format!("Could not spawn thread: {}", err)))?;
+
+    let result = match timeout {
+        Some(timeout) => receiver.recv_timeout(timeout).map_err(|err| match err {
+            RecvTimeoutError::Timeout => SolveError::Timeout(timeout),
+            RecvTimeoutError::Disconnected => SolveError::Panic(String::from("thread exited")),
+        })?,
+        None => receiver
+            .recv()
+            .map_err(|_| SolveError::Panic(String::from("thread exited")))?,
+    };
+
+    match result {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(err)) => Err(SolveError::Error(err.to_string())),
+        Err(message) => Err(SolveError::Panic(message)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_ok() {
+        assert_eq!(run(None, &Workers::default(), || Ok(5)), Ok(5));
+    }
+
+    #[test]
+    fn test_run_error() {
+        let result: Result<(), _> =
+            run(None, &Workers::default(), || anyhow::bail!("Id not found!"));
+
+        assert_eq!(
+            result,
+            Err(SolveError::Error(String::from("Id not found!")))
+        );
+    }
+
+    #[test]
+    fn test_run_panic() {
+        let result: Result<(), _> = run(None, &Workers::default(), || panic!("bad seat {}", 7));
+
+        match result {
+            Err(SolveError::Panic(message)) => {
+                assert!(message.starts_with("bad seat 7 at src/isolate.rs:"))
+            }
+            result => panic!("Expected a panic, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_run_timeout() {
+        let timeout = Duration::from_millis(20);
+        let workers = Workers::default();
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let result = run(Some(timeout), &workers, move || loop {
+            if stopped.try_recv().is_ok() {
+                return Ok(());
+            }
+
+            thread::sleep(Duration::from_millis(5));
+        });
+
+        assert_eq!(result, Err(SolveError::Timeout(timeout)));
+        assert_eq!(workers.running(), 1);
+
+        // The thread is counted until it really finishes.
+        stop.send(()).unwrap();
+
+        while workers.running() > 0 {
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        assert_eq!(run(None, &workers, || Ok(5)), Ok(5));
+        assert_eq!(workers.running(), 0);
+    }
+}
@@ -0,0 +1,428 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+
+use advent_of_code_2020_rs::answer::Answer;
+use anyhow::{anyhow, bail};
+
+/// A JSON value, just enough to serialise runner results, and read them back,
+/// without pulling in a serialisation framework.
+///
+/// Numbers are integers only, since nothing the runner writes has a fraction.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Integer(i128),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// The value of `key`, if this is an object that has it.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Self::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            Self::Integer(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+macro_rules! from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Value {
+                fn from(n: $t) -> Self {
+                    Self::Integer(n as i128)
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(i32, i64, u32, u64, usize);
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Self::String(String::from(s))
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+/// Integers and strings map to their JSON counterparts, lists to arrays and
+/// grids to arrays of rows.
+impl From<&Answer> for Value {
+    fn from(answer: &Answer) -> Self {
+        match answer {
+            Answer::Integer(n) => Self::Integer(*n),
+            Answer::String(s) => Self::String(s.clone()),
+            Answer::List(items) => Self::Array(items.iter().map(Into::into).collect()),
+            Answer::Grid(rows) => Self::Array(rows.iter().cloned().map(Into::into).collect()),
+        }
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(option: Option<T>) -> Self {
+        option.map_or(Self::Null, Into::into)
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+
+    f.write_str("\"")
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Integer(n) => write!(f, "{}", n),
+            Self::String(s) => write_string(f, s),
+            Self::Array(values) => {
+                f.write_str("[")?;
+
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+
+                    write!(f, "{}", value)?;
+                }
+
+                f.write_str("]")
+            }
+            Self::Object(fields) => {
+                f.write_str("{")?;
+
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Reads a single value out of JSON text, keeping track of the position for
+/// error messages.
+struct Parser<'a> {
+    s: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Self {
+            s,
+            chars: s.char_indices().peekable(),
+        }
+    }
+
+    fn position(&mut self) -> usize {
+        self.chars.peek().map_or(self.s.len(), |&(i, _)| i)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some((_, ' ' | '\t' | '\n' | '\r')) = self.chars.peek() {
+            self.chars.next();
+        }
+    }
+
+    fn next_char(&mut self) -> anyhow::Result<char> {
+        self.chars
+            .next()
+            .map(|(_, c)| c)
+            .ok_or_else(|| anyhow!("Unexpected end of JSON"))
+    }
+
+    fn expect(&mut self, expected: char) -> anyhow::Result<()> {
+        let at = self.position();
+
+        match self.next_char()? {
+            c if c == expected => Ok(()),
+            c => bail!("Expected '{}' at {}, found '{}'", expected, at, c),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Value) -> anyhow::Result<Value> {
+        for expected in keyword.chars() {
+            self.expect(expected)?;
+        }
+
+        Ok(value)
+    }
+
+    fn value(&mut self) -> anyhow::Result<Value> {
+        self.skip_whitespace();
+
+        let value = match self.chars.peek().map(|&(_, c)| c) {
+            Some('n') => self.keyword("null", Value::Null)?,
+            Some('t') => self.keyword("true", Value::Bool(true))?,
+            Some('f') => self.keyword("false", Value::Bool(false))?,
+            Some('"') => Value::String(self.string()?),
+            Some('[') => self.array()?,
+            Some('{') => self.object()?,
+            Some('-' | '0'..='9') => self.integer()?,
+            Some(c) => bail!("Unexpected '{}' at {}", c, self.position()),
+            None => bail!("Unexpected end of JSON"),
+        };
+
+        self.skip_whitespace();
+
+        Ok(value)
+    }
+
+    fn integer(&mut self) -> anyhow::Result<Value> {
+        let start = self.position();
+
+        if let Some((_, '-')) = self.chars.peek() {
+            self.chars.next();
+        }
+
+        while let Some((_, '0'..='9')) = self.chars.peek() {
+            self.chars.next();
+        }
+
+        let end = self.position();
+        let text = &self.s[start..end];
+
+        text.parse()
+            .map(Value::Integer)
+            .map_err(|_| anyhow!("Invalid integer at {}: {}", start, text))
+    }
+
+    fn string(&mut self) -> anyhow::Result<String> {
+        self.expect('"')?;
+
+        let mut s = String::new();
+
+        loop {
+            match self.next_char()? {
+                '"' => return Ok(s),
+                '\\' => {
+                    let at = self.position();
+
+                    let c = match self.next_char()? {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => {
+                            let code = (0..4)
+                                .map(|_| self.next_char())
+                                .collect::<anyhow::Result<String>>()?;
+
+                            u32::from_str_radix(&code, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| anyhow!("Invalid escape at {}: \\u{}", at, code))?
+                        }
+                        c => bail!("Invalid escape at {}: \\{}", at, c),
+                    };
+
+                    s.push(c);
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    /// Parses the items of an array or object, from `open` to `close`.
+    fn items<T, F>(&mut self, open: char, close: char, mut item: F) -> anyhow::Result<Vec<T>>
+    where
+        F: FnMut(&mut Self) -> anyhow::Result<T>,
+    {
+        self.expect(open)?;
+        self.skip_whitespace();
+
+        let mut items = Vec::new();
+
+        if let Some(&(_, c)) = self.chars.peek() {
+            if c == close {
+                self.chars.next();
+                return Ok(items);
+            }
+        }
+
+        loop {
+            items.push(item(self)?);
+
+            let at = self.position();
+
+            match self.next_char()? {
+                ',' => {}
+                c if c == close => return Ok(items),
+                c => bail!("Expected ',' or '{}' at {}, found '{}'", close, at, c),
+            }
+        }
+    }
+
+    fn array(&mut self) -> anyhow::Result<Value> {
+        self.items('[', ']', Self::value).map(Value::Array)
+    }
+
+    fn object(&mut self) -> anyhow::Result<Value> {
+        let fields = self.items('{', '}', |parser| {
+            parser.skip_whitespace();
+            let key = parser.string()?;
+            parser.skip_whitespace();
+            parser.expect(':')?;
+
+            Ok((key, parser.value()?))
+        })?;
+
+        Ok(Value::Object(fields))
+    }
+}
+
+impl FromStr for Value {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let value = parser.value()?;
+
+        if let Some((i, _)) = parser.chars.next() {
+            bail!("Unexpected trailing characters at {}", i);
+        }
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialise() {
+        let value = Value::Object(vec![
+            (String::from("day"), Value::from(7)),
+            (String::from("answer"), Value::from("a \"quoted\"\nline")),
+            (String::from("error"), Value::from(None::<String>)),
+            (String::from("flag"), Value::from(true)),
+            (String::from("offset"), Value::from(-3)),
+        ]);
+
+        assert_eq!(
+            value.to_string(),
+            r#"{"day":7,"answer":"a \"quoted\"\nline","error":null,"flag":true,"offset":-3}"#
+        );
+    }
+
+    #[test]
+    fn test_serialise_answers() {
+        let list = Answer::from(vec![3, 4]);
+        let grid = Answer::grid(vec!["#.", ".#"]);
+
+        assert_eq!(Value::from(&list).to_string(), "[3,4]");
+        assert_eq!(Value::from(&grid).to_string(), r##"["#.",".#"]"##);
+    }
+
+    #[test]
+    fn test_serialise_control_characters() {
+        assert_eq!(Value::from("\u{1}\t").to_string(), r#""\u0001\t""#);
+    }
+
+    #[test]
+    fn test_parse() {
+        let text =
+            r#"{"day":7,"answer":"a \"quoted\"\nline","error":null,"flag":true,"offset":-3}"#;
+        let value: Value = text.parse().unwrap();
+
+        assert_eq!(value.to_string(), text);
+        assert_eq!(value.get("day").and_then(Value::as_integer), Some(7));
+        assert_eq!(
+            value.get("answer").and_then(Value::as_str),
+            Some("a \"quoted\"\nline")
+        );
+        assert_eq!(value.get("missing"), None);
+
+        let value: Value = r#" [ 1, [], {}, "\u00e9\t" ] "#.parse().unwrap();
+
+        assert_eq!(
+            value,
+            Value::Array(vec![
+                Value::Integer(1),
+                Value::Array(vec![]),
+                Value::Object(vec![]),
+                Value::from("é\t"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for text in &[
+            "",
+            "[1,",
+            "[1 2]",
+            "{\"a\" 1}",
+            "\"\\x\"",
+            "1.5",
+            "nul",
+            "[] x",
+            "-",
+        ] {
+            assert!(text.parse::<Value>().is_err(), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Solutions to Advent of Code 2020.
+//!
+//! Each `dayN` module exposes its puzzle's domain types, a `parse_input`
+//! function and the `part1`/`part2` solvers. The [`solution`] module ties
+//! them together for the runner, every day reads its input through
+//! [`input`], and every part's result becomes an [`answer::Answer`].
+//!
+//! Answers are widened to `i64` wherever their inputs are `i32`, and anything
+//! that could still overflow uses checked arithmetic and fails with an error.
+
+pub mod answer;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+#[cfg(test)]
+mod fuzz;
+pub mod generate;
+pub mod input;
+pub mod parse;
+pub mod solution;
@@ -1,96 +1,160 @@
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-
-use std::path::PathBuf;
-
-const INPUT_DIR: &str = "input";
-
-fn input_dir() -> PathBuf {
-    use std::env;
-
-    env::args()
-        .skip(1)
-        .next()
-        .unwrap_or(INPUT_DIR.to_string())
-        .into()
-}
-
-fn day<F>(day: i32, parts: F)
-where
-    F: Fn(&str),
-{
-    use std::fs;
-
-    println!("Day: {}", day);
-
-    let mut path = input_dir();
-    path.push(format!("day{}", day));
-
-    fs::read_to_string(&path)
-        .map(|input| parts(&input))
-        .unwrap_or_else(|err| {
-            println!("Error opening input file {}: {}", path.to_str().unwrap(), err);
-        });
-}
-
-fn part<F, O, E>(part: i32, f: F, input: &str)
-where
-    F: Fn(&str) -> Result<O, E>,
-    O: std::fmt::Display,
-    E: Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
-{
-    println!("  Part {}:", part);
-
-    match f(input) {
-        Ok(output) => println!("    Output: {}", output),
-        Err(err) => println!("    Error: {}", err.into()),
-    };
-}
+mod answers;
+mod args;
+mod bench;
+mod cache;
+mod config;
+mod fetch;
+mod history;
+mod isolate;
+mod json;
+mod output;
+mod runner;
+mod scaffold;
+mod watch;
+
+use std::path::Path;
+use std::process;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use advent_of_code_2020_rs::{generate, solution};
+
+use args::{Args, Command};
+use config::Config;
+use output::Printer;
 
 fn main() {
-    day(1, |input| {
-        part(1, day1::part1, input);
-        part(2, day1::part2, input);
-    });
-
-    day(2, |input| {
-        part(1, day2::part1, input);
-        part(2, day2::part2, input);
-    });
-
-    day(3, |input| {
-        part(1, day3::part1, input);
-        part(2, day3::part2, input);
-    });
+    use std::env;
 
-    day(4, |input| {
-        part(1, day4::part1, input);
-        part(2, day4::part2, input);
+    let config = Config::load(Path::new(config::CONFIG_FILE)).unwrap_or_else(|err| {
+        eprintln!("Error: {:#}", err);
+        process::exit(2);
     });
 
-    day(5, |input| {
-        part(1, day5::part1, input);
-        part(2, day5::part2, input);
+    let mut args = Args::parse(config.defaults(), env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("Error: {}\n\n{}", err, args::USAGE);
+        process::exit(2);
     });
 
-    day(6, |input| {
-        part(1, day6::part1, input);
-        part(2, day6::part2, input);
-    });
+    args.session = env::var("AOC_SESSION").ok();
+
+    if args.help {
+        println!("{}", args::USAGE);
+        return;
+    }
+
+    if let Command::NewDay { day, name } = &args.command {
+        match scaffold::new_day(&args.input_dir, *day, name.as_deref()) {
+            Ok(paths) => {
+                for path in paths {
+                    println!("Wrote {}", path.display());
+                }
+            }
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                process::exit(1);
+            }
+        }
+
+        return;
+    }
+
+    if let Command::Generate { day, size, seed } = args.command {
+        // Without a seed, pick one and report it so the input can be made
+        // again.
+        let seed = seed.unwrap_or_else(|| {
+            let seed = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos() as u64);
+
+            eprintln!("Seed: {}", seed);
+            seed
+        });
 
-    day(7, |input| {
-        part(1, day7::part1, input);
-        part(2, day7::part2, input);
+        match generate::input(day, size, &mut generate::Rng::new(seed)) {
+            Some(input) => print!("{}", input),
+            None => {
+                eprintln!("Error: Day {} has no input generator", day);
+                process::exit(2);
+            }
+        }
+
+        return;
+    }
+
+    if let Some(days) = &args.days {
+        if let Some(day) = days.iter().find(|day| solution::find(**day).is_none()) {
+            eprintln!("Error: Day {} is not implemented", day);
+            process::exit(2);
+        }
+    }
+
+    let solutions: Vec<_> = solution::SOLUTIONS
+        .iter()
+        .copied()
+        .filter(|solution| args.runs_day(solution.day()))
+        .collect();
+
+    if let Err(err) = runner::check_strategies(&args, &solutions) {
+        eprintln!("Error: {}", err);
+        process::exit(2);
+    }
+
+    // Hashing the executable takes a moment, so skip it when nothing will
+    // be cached.
+    if args.uses_cache() {
+        args.build = cache::build_id();
+    }
+
+    let printer = Printer::new(args.format, args.shows_time());
+    printer.print_header();
+
+    let tasks = runner::tasks(&args, &solutions);
+
+    if args.watch {
+        watch::run(&args, &tasks, &printer);
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+
+    let mut run = history::Run::new(now, args.label.clone());
+
+    let start = Instant::now();
+    let mut passed = true;
+
+    runner::run_days(&args, &tasks, |report| {
+        passed &= report.passed();
+        printer.print(&report);
+        run.add(&report);
     });
 
-    day(8, |input| {
-        part(1, day8::part1, input);
-        part(2, day8::part2, input);
-    });
+    printer.print_total(start.elapsed());
+
+    if let Some(baseline) = &args.compare {
+        let unchanged = history::load(&args.history)
+            .and_then(|runs| {
+                let deltas = history::compare(&runs, baseline, &run, args.threshold)?;
+
+                printer.print_comparison(&deltas);
+                Ok(deltas.iter().all(|delta| !delta.flagged()))
+            })
+            .unwrap_or_else(|err| {
+                eprintln!("Error: {}", err);
+                process::exit(2);
+            });
+
+        passed &= unchanged;
+    }
+
+    if args.record {
+        if let Err(err) = history::append(&args.history, &run) {
+            eprintln!("Error: {}", err);
+            process::exit(2);
+        }
+    }
+
+    if !passed {
+        process::exit(1);
+    }
 }
@@ -0,0 +1,456 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use advent_of_code_2020_rs::answer::Answer;
+use advent_of_code_2020_rs::parse::ParseError;
+use advent_of_code_2020_rs::solution::DEFAULT_STRATEGY;
+use anyhow::bail;
+
+use crate::answers::Verdict;
+use crate::history::Delta;
+use crate::isolate::SolveError;
+use crate::json::Value;
+use crate::runner::DayReport;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// The indented, human readable layout.
+    Text,
+    /// One JSON object per line for each (day, part).
+    Json,
+    /// One CSV row for each (day, part), after a header row.
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let format = match s {
+            "text" => Self::Text,
+            "json" => Self::Json,
+            "csv" => Self::Csv,
+            _ => bail!("Unrecognized output format: {}", s),
+        };
+
+        Ok(format)
+    }
+}
+
+const CSV_HEADER: &str =
+    "day,name,example,part,strategy,status,cached,answer,error,check,expected,runs,parse_ns,min_ns,median_ns,p95_ns";
+
+/// A flattened (day, part) result, shared by the machine readable formats.
+struct Record<'a> {
+    day: u32,
+    name: &'a str,
+    example: Option<&'a str>,
+    part: u32,
+    strategy: Option<&'a str>,
+    status: &'static str,
+    cached: bool,
+    answer: Option<&'a Answer>,
+    error: Option<String>,
+    verdict: Option<&'a Verdict>,
+    runs: Option<usize>,
+    parse_ns: Option<u128>,
+    min_ns: Option<u128>,
+    median_ns: Option<u128>,
+    p95_ns: Option<u128>,
+}
+
+impl Record<'_> {
+    fn check(&self) -> Option<&'static str> {
+        self.verdict.map(Verdict::as_str)
+    }
+
+    fn expected(&self) -> Option<&str> {
+        match self.verdict {
+            Some(Verdict::Fail { expected }) => Some(expected),
+            _ => None,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let nanos = |n: Option<u128>| n.map_or(Value::Null, |n| Value::Integer(n as i128));
+
+        Value::Object(vec![
+            (String::from("day"), self.day.into()),
+            (String::from("name"), self.name.into()),
+            (String::from("example"), self.example.into()),
+            (String::from("part"), self.part.into()),
+            (String::from("strategy"), self.strategy.into()),
+            (String::from("status"), self.status.into()),
+            (String::from("cached"), self.cached.into()),
+            (String::from("answer"), self.answer.map(Value::from).into()),
+            (String::from("error"), self.error.clone().into()),
+            (String::from("check"), self.check().into()),
+            (String::from("expected"), self.expected().into()),
+            (String::from("runs"), self.runs.into()),
+            (String::from("parse_ns"), nanos(self.parse_ns)),
+            (String::from("min_ns"), nanos(self.min_ns)),
+            (String::from("median_ns"), nanos(self.median_ns)),
+            (String::from("p95_ns"), nanos(self.p95_ns)),
+        ])
+    }
+
+    fn to_csv(&self) -> String {
+        let number = |n: Option<u128>| n.map(|n| n.to_string()).unwrap_or_default();
+
+        [
+            self.day.to_string(),
+            csv_field(self.name),
+            csv_field(self.example.unwrap_or_default()),
+            self.part.to_string(),
+            csv_field(self.strategy.unwrap_or_default()),
+            String::from(self.status),
+            self.cached.to_string(),
+            csv_field(&self.answer.map(Answer::to_string).unwrap_or_default()),
+            csv_field(self.error.as_deref().unwrap_or_default()),
+            String::from(self.check().unwrap_or_default()),
+            csv_field(self.expected().unwrap_or_default()),
+            self.runs.map(|n| n.to_string()).unwrap_or_default(),
+            number(self.parse_ns),
+            number(self.min_ns),
+            number(self.median_ns),
+            number(self.p95_ns),
+        ]
+        .join(",")
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        String::from(s)
+    }
+}
+
+fn skipped(err: &ParseError) -> String {
+    format!("Skipped line {} ({}): {}", err.line, err.text, err.message)
+}
+
+/// Machine readable formats report skipped records on stderr, so stdout only
+/// holds records.
+fn print_skipped(report: &DayReport) {
+    for err in &report.rejected {
+        eprintln!("Day {}: {}", report.day, skipped(err));
+    }
+}
+
+fn records(report: &DayReport) -> Vec<Record<'_>> {
+    let parse_ns = report.parse_time.map(|stats| stats.median.as_nanos());
+
+    if let Some(failure) = &report.failure {
+        return report
+            .requested_parts
+            .iter()
+            .map(|&part| Record {
+                day: report.day,
+                name: report.name,
+                example: report.example.as_deref(),
+                part,
+                strategy: None,
+                status: "error",
+                cached: false,
+                answer: None,
+                error: Some(failure.to_string()),
+                verdict: None,
+                runs: None,
+                parse_ns,
+                min_ns: None,
+                median_ns: None,
+                p95_ns: None,
+            })
+            .collect();
+    }
+
+    report
+        .parts
+        .iter()
+        .map(|part| Record {
+            day: report.day,
+            name: report.name,
+            example: report.example.as_deref(),
+            part: part.part,
+            strategy: Some(part.strategy),
+            status: part.result.as_ref().err().map_or("ok", SolveError::status),
+            cached: part.cached,
+            answer: part.result.as_ref().ok(),
+            error: part.result.as_ref().err().map(SolveError::to_string),
+            verdict: part.verdict.as_ref(),
+            runs: part.time.map(|time| time.runs),
+            parse_ns,
+            min_ns: part.time.map(|time| time.min.as_nanos()),
+            median_ns: part.time.map(|time| time.median.as_nanos()),
+            p95_ns: part.time.map(|time| time.p95.as_nanos()),
+        })
+        .collect()
+}
+
+/// Writes day reports to stdout in the chosen format.
+pub struct Printer {
+    format: Format,
+    show_time: bool,
+}
+
+impl Printer {
+    pub fn new(format: Format, show_time: bool) -> Self {
+        Self { format, show_time }
+    }
+
+    /// Starts the output with the CSV header row. Other formats have none.
+    pub fn print_header(&self) {
+        if self.format == Format::Csv {
+            println!("{}", CSV_HEADER);
+        }
+    }
+
+    pub fn print(&self, report: &DayReport) {
+        match self.format {
+            Format::Text => self.print_text(report),
+            Format::Json => {
+                print_skipped(report);
+
+                for record in records(report) {
+                    println!("{}", record.to_json());
+                }
+            }
+            Format::Csv => {
+                print_skipped(report);
+
+                for record in records(report) {
+                    println!("{}", record.to_csv());
+                }
+            }
+        }
+    }
+
+    /// Reports the wall time of the whole run. Machine readable formats write
+    /// it to stderr so stdout only holds records.
+    pub fn print_total(&self, elapsed: Duration) {
+        match self.format {
+            Format::Text => println!("Total: {:.2?}", elapsed),
+            Format::Json | Format::Csv => eprintln!("Total: {:.2?}", elapsed),
+        }
+    }
+
+    /// Reports progress that isn't part of any day's results, such as what
+    /// `--watch` is doing, on stdout or, for machine readable formats,
+    /// stderr.
+    pub fn print_status(&self, status: &str) {
+        match self.format {
+            Format::Text => println!("{}", status),
+            Format::Json | Format::Csv => eprintln!("{}", status),
+        }
+    }
+
+    /// Reports how each part compares with the history, on stdout or, for
+    /// machine readable formats, stderr.
+    pub fn print_comparison(&self, deltas: &[Delta]) {
+        let mut text = String::from("Compared with history:");
+
+        for delta in deltas {
+            text.push_str(&format!("\n  {}", delta));
+        }
+
+        match self.format {
+            Format::Text => println!("{}", text),
+            Format::Json | Format::Csv => eprintln!("{}", text),
+        }
+    }
+
+    fn print_text(&self, report: &DayReport) {
+        match &report.example {
+            Some(example) => println!("Day: {} ({}), example {}", report.day, report.name, example),
+            None => println!("Day: {} ({})", report.day, report.name),
+        }
+
+        if let (true, Some(parse_time)) = (self.show_time, report.parse_time) {
+            println!("  Parse: {}", parse_time);
+        }
+
+        for err in &report.rejected {
+            println!("  {}", skipped(err));
+        }
+
+        if let Some(failure) = &report.failure {
+            // Parse errors span several lines, which all belong under the day.
+            println!("  {}", failure.to_string().replace('\n', "\n  "));
+            return;
+        }
+
+        for part in &report.parts {
+            let mut notes = Vec::new();
+
+            if part.strategy != DEFAULT_STRATEGY {
+                notes.push(part.strategy);
+            }
+
+            if part.cached {
+                notes.push("cached");
+            }
+
+            if notes.is_empty() {
+                println!("  Part {}:", part.part);
+            } else {
+                println!("  Part {} ({}):", part.part, notes.join(", "));
+            }
+
+            match &part.result {
+                // Grids and other multi-line answers start on their own line,
+                // so their rows stay lined up.
+                Ok(output) if output.is_multiline() => {
+                    println!(
+                        "    Output:\n      {}",
+                        output.to_string().replace('\n', "\n      ")
+                    )
+                }
+                Ok(output) => println!("    Output: {}", output),
+                Err(err) => println!("    Error: {}", err),
+            };
+
+            if let Some(verdict) = &part.verdict {
+                println!(
+                    "    Check: {}",
+                    verdict.to_string().replace('\n', "\n      ")
+                );
+            }
+
+            if let (true, Some(time)) = (self.show_time, part.time) {
+                println!("    Time: {}", time);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use crate::bench::Stats;
+    use crate::runner::{Failure, PartReport};
+
+    fn stats() -> Stats {
+        Stats::from_samples(vec![Duration::from_nanos(1500)])
+    }
+
+    fn report() -> DayReport {
+        DayReport {
+            day: 7,
+            name: "Handy Haversacks",
+            example: None,
+            requested_parts: vec![1, 2],
+            parse_time: Some(stats()),
+            rejected: Vec::new(),
+            failure: None,
+            parts: vec![
+                PartReport {
+                    part: 1,
+                    strategy: "reverse-index",
+                    result: Ok(Answer::from(4)),
+                    verdict: Some(Verdict::Fail {
+                        expected: String::from("5"),
+                    }),
+                    time: Some(stats()),
+                    cached: false,
+                },
+                PartReport {
+                    part: 2,
+                    strategy: "default",
+                    result: Err(SolveError::Error(String::from(
+                        "Could not find entry for x, y",
+                    ))),
+                    verdict: None,
+                    time: None,
+                    cached: false,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("json".parse::<Format>().unwrap(), Format::Json);
+        assert!("xml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn test_json_records() {
+        let report = report();
+        let json: Vec<String> = records(&report)
+            .iter()
+            .map(|record| record.to_json().to_string())
+            .collect();
+
+        assert_eq!(
+            json,
+            vec![
+                r#"{"day":7,"name":"Handy Haversacks","example":null,"part":1,"strategy":"reverse-index","status":"ok","cached":false,"answer":4,"error":null,"check":"FAIL","expected":"5","runs":1,"parse_ns":1500,"min_ns":1500,"median_ns":1500,"p95_ns":1500}"#,
+                r#"{"day":7,"name":"Handy Haversacks","example":null,"part":2,"strategy":"default","status":"error","cached":false,"answer":null,"error":"Could not find entry for x, y","check":null,"expected":null,"runs":null,"parse_ns":1500,"min_ns":null,"median_ns":null,"p95_ns":null}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_csv_records() {
+        let report = report();
+        let csv: Vec<String> = records(&report).iter().map(Record::to_csv).collect();
+
+        assert_eq!(
+            csv,
+            vec![
+                "7,Handy Haversacks,,1,reverse-index,ok,false,4,,FAIL,5,1,1500,1500,1500,1500",
+                "7,Handy Haversacks,,2,default,error,false,,\"Could not find entry for x, y\",,,,1500,,,",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_structured_answer_records() {
+        let mut report = report();
+        report.parts[0].result = Ok(Answer::grid(vec!["#.", ".#"]));
+        report.parts[1].result = Ok(Answer::from(vec![3, 4]));
+        report.parts[1].cached = true;
+
+        let answers: Vec<String> = records(&report)
+            .iter()
+            .map(|record| {
+                let json = record.to_json().to_string();
+                let start = json.find("\"answer\":").unwrap();
+                let end = json.find(",\"error\"").unwrap();
+
+                format!("{} | {}", &json[start..end], record.to_csv())
+            })
+            .collect();
+
+        assert_eq!(
+            answers,
+            vec![
+                "\"answer\":[\"#.\",\".#\"] | 7,Handy Haversacks,,1,reverse-index,ok,false,\"#.\n.#\",,FAIL,5,1,1500,1500,1500,1500",
+                "\"answer\":[3,4] | 7,Handy Haversacks,,2,default,ok,true,\"3,4\",,,,,1500,,,",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_day_records() {
+        let mut report = report();
+        report.parts.clear();
+        report.parse_time = None;
+        report.failure = Some(Failure::Input(String::from("input/day7: missing")));
+
+        let csv: Vec<String> = records(&report).iter().map(Record::to_csv).collect();
+
+        assert_eq!(
+            csv,
+            vec![
+                "7,Handy Haversacks,,1,,error,false,,Error opening input file input/day7: missing,,,,,,,",
+                "7,Handy Haversacks,,2,,error,false,,Error opening input file input/day7: missing,,,,,,,",
+            ]
+        );
+    }
+}
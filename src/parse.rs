@@ -0,0 +1,184 @@
+//! Errors for puzzle input that could not be parsed.
+
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// Where in the input a parser gave up, and why.
+///
+/// Parsers create these against the string they were handed, pointing at a
+/// slice of it. Callers that handed out that string as a slice of something
+/// larger, such as one line of the whole input, move the error into their own
+/// coordinates with [`ParseError::within`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// The line the error is on, starting at 1.
+    pub line: usize,
+    /// The characters of the line the error covers, starting at 0.
+    pub columns: Range<usize>,
+    /// The whole line the error is on.
+    pub text: String,
+    pub message: String,
+}
+
+/// The byte range of `part` within `source`, or all of `source` if `part`
+/// is not a slice of it.
+fn byte_range(source: &str, part: &str) -> Range<usize> {
+    let start = (part.as_ptr() as usize).wrapping_sub(source.as_ptr() as usize);
+
+    match start.checked_add(part.len()) {
+        Some(end) if end <= source.len() => start..end,
+        _ => 0..source.len(),
+    }
+}
+
+/// The line number of byte `offset`, the byte range of that line and the
+/// column of `offset` in characters.
+fn locate(source: &str, offset: usize) -> (usize, Range<usize>, usize) {
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[offset..]
+        .find('\n')
+        .map_or(source.len(), |i| offset + i);
+
+    let line = source[..line_start].matches('\n').count() + 1;
+    let column = source[line_start..offset].chars().count();
+
+    (line, line_start..line_end, column)
+}
+
+impl ParseError {
+    /// An error about `span`, which must be a slice of `source`. A span that
+    /// runs over several lines is cut off at the end of its first one.
+    pub fn new(source: &str, span: &str, message: impl Into<String>) -> Self {
+        let bytes = byte_range(source, span);
+        let (line, line_bytes, column) = locate(source, bytes.start);
+
+        let end = bytes.end.min(line_bytes.end);
+        let width = source[bytes.start..end].chars().count();
+
+        Self {
+            line,
+            columns: column..(column + width),
+            text: source[line_bytes].trim_end_matches('\r').to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Moves an error reported against `part` into the coordinates of
+    /// `source`, where `part` is a slice of `source`.
+    pub fn within(self, source: &str, part: &str) -> Self {
+        let bytes = byte_range(source, part);
+        let (first_line, _, first_column) = locate(source, bytes.start);
+
+        let line = first_line + self.line - 1;
+
+        let columns = if self.line == 1 {
+            (self.columns.start + first_column)..(self.columns.end + first_column)
+        } else {
+            self.columns
+        };
+
+        let text = source
+            .lines()
+            .nth(line - 1)
+            .unwrap_or_default()
+            .trim_end_matches('\r')
+            .to_string();
+
+        Self {
+            line,
+            columns,
+            text,
+            message: self.message,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        let carets = self.columns.len().max(1);
+
+        writeln!(f, "{}", self.message)?;
+        writeln!(
+            f,
+            "{}--> line {}, column {}",
+            gutter,
+            self.line,
+            self.columns.start + 1
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", number, self.text)?;
+        write!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(self.columns.start),
+            "^".repeat(carets)
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    #[test]
+    fn test_new() {
+        let source = "nop +0\njpm +4\n";
+        let error = ParseError::new(source, &source[7..10], "Invalid instruction: jpm");
+
+        assert_eq!(
+            error,
+            ParseError {
+                line: 2,
+                columns: 0..3,
+                text: String::from("jpm +4"),
+                message: String::from("Invalid instruction: jpm"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_within() {
+        let source = "nop +0\nacc +1\njmp x4\n";
+        let line = source.lines().nth(2).unwrap();
+
+        let error = ParseError::new(line, &line[4..], "Invalid number").within(source, line);
+
+        assert_eq!(error.line, 3);
+        assert_eq!(error.columns, 4..6);
+        assert_eq!(error.text, "jmp x4");
+    }
+
+    #[test]
+    fn test_not_a_slice() {
+        let error = ParseError::new("abc", "elsewhere", "Oops");
+
+        assert_eq!(error.line, 1);
+        assert_eq!(error.columns, 0..3);
+    }
+
+    #[test]
+    fn test_display() {
+        let source = "1-3 a: abcde\n1-x b: cdefg\n";
+        let line = source.lines().nth(1).unwrap();
+        let error = ParseError::new(source, &line[2..3], "Invalid number: x");
+
+        assert_eq!(
+            error.to_string(),
+            indoc! {"
+                Invalid number: x
+                 --> line 2, column 3
+                  |
+                2 | 1-x b: cdefg
+                  |   ^"}
+        );
+    }
+}
@@ -0,0 +1,790 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read as _};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use advent_of_code_2020_rs::answer::Answer;
+use advent_of_code_2020_rs::input::PuzzleInput;
+use advent_of_code_2020_rs::parse::ParseError;
+use advent_of_code_2020_rs::solution::{DynSolution, ParsedInput};
+use anyhow::bail;
+
+use crate::answers::{Answers, Verdict};
+use crate::args::Args;
+use crate::bench::{self, Stats};
+use crate::cache;
+use crate::config;
+use crate::fetch::{Fetch, HttpFetcher, InputProvider};
+use crate::isolate::{self, SolveError, Workers};
+
+const EXAMPLES_DIR: &str = "examples";
+
+/// Why a day could not get as far as running its parts.
+#[derive(Debug)]
+pub enum Failure {
+    Input(String),
+    Answers(String),
+    Parse(String),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Input(err) => write!(f, "Error opening input file {}", err),
+            Self::Answers(err) => write!(f, "Error reading answers file {}", err),
+            Self::Parse(err) => write!(f, "Error parsing input: {}", err),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PartReport {
+    pub part: u32,
+    /// The strategy the answer came from.
+    pub strategy: &'static str,
+    pub result: Result<Answer, SolveError>,
+    /// How the answer compares with the answers file, if the part succeeded.
+    pub verdict: Option<Verdict>,
+    /// How long the part took, if it succeeded.
+    pub time: Option<Stats>,
+    /// Whether the answer came from the cache rather than being solved.
+    pub cached: bool,
+}
+
+#[derive(Debug)]
+pub struct DayReport {
+    pub day: u32,
+    pub name: &'static str,
+    /// Which example the day ran on, if it ran on one rather than the puzzle
+    /// input.
+    pub example: Option<String>,
+    /// The parts that were asked for, whether or not they ran.
+    pub requested_parts: Vec<u32>,
+    pub parse_time: Option<Stats>,
+    /// Records skipped by a lenient parse.
+    pub rejected: Vec<ParseError>,
+    pub failure: Option<Failure>,
+    pub parts: Vec<PartReport>,
+}
+
+impl DayReport {
+    /// Whether every requested part ran without error and no answer
+    /// contradicted the answers file.
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+            && self.parts.iter().all(|part| {
+                part.result.is_ok() && !matches!(part.verdict, Some(Verdict::Fail { .. }))
+            })
+    }
+}
+
+/// A day to run, on either its puzzle input or one of its examples.
+#[derive(Clone)]
+pub struct Task {
+    pub solution: &'static dyn DynSolution,
+    /// An example input file, read instead of the puzzle input.
+    pub example: Option<PathBuf>,
+    /// The threads parsing and solving this task, which outlive the run if
+    /// they time out.
+    pub workers: Workers,
+}
+
+impl Task {
+    /// Where the input for this task is read from.
+    pub fn input_path(&self, args: &Args) -> PathBuf {
+        match &self.example {
+            Some(path) => path.clone(),
+            None => args.input_path(self.solution.day()),
+        }
+    }
+}
+
+/// The example inputs for `day`, `examples/dayN/*.txt`, ordered by number.
+fn example_paths(day: u32) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+
+    for entry in fs::read_dir(Path::new(EXAMPLES_DIR).join(format!("day{}", day)))? {
+        let path = entry?.path();
+
+        if path.extension().is_some_and(|extension| extension == "txt") {
+            paths.push(path);
+        }
+    }
+
+    paths.sort_by_key(|path| {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        (stem.parse::<u32>().ok(), stem.into_owned())
+    });
+
+    Ok(paths)
+}
+
+/// Builds the tasks for `solutions`: one per day, or with `--example` one per
+/// example of each day.
+pub fn tasks(args: &Args, solutions: &[&'static dyn DynSolution]) -> Vec<Task> {
+    let mut tasks = Vec::new();
+
+    for &solution in solutions {
+        if !args.example {
+            tasks.push(Task {
+                solution,
+                example: None,
+                workers: Workers::default(),
+            });
+            continue;
+        }
+
+        let paths = example_paths(solution.day()).unwrap_or_default();
+
+        // A day without examples gets a task for its first one anyway, so the
+        // missing file is reported like a missing input.
+        let paths = if paths.is_empty() {
+            vec![Path::new(EXAMPLES_DIR)
+                .join(format!("day{}", solution.day()))
+                .join("1.txt")]
+        } else {
+            paths
+        };
+
+        tasks.extend(paths.into_iter().map(|path| Task {
+            solution,
+            example: Some(path),
+            workers: Workers::default(),
+        }));
+    }
+
+    tasks
+}
+
+/// Reads the puzzle input for `day`, from the file or stdin given with
+/// `--input`, or else from the input directory, downloading it if fetching
+/// is enabled.
+fn read_input(args: &Args, task: &Task) -> anyhow::Result<String> {
+    if let Some(path) = &task.example {
+        return Ok(fs::read_to_string(path)?);
+    }
+
+    match &args.input {
+        Some(path) if path == Path::new("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+
+            Ok(input)
+        }
+        Some(path) => Ok(fs::read_to_string(path)?),
+        None => {
+            let fetcher = args
+                .fetch_url
+                .as_ref()
+                .map(|url| HttpFetcher::new(url, args.session.clone()));
+
+            let fetcher = fetcher.as_ref().map(|fetcher| fetcher as &dyn Fetch);
+
+            InputProvider::new(args.input_dir.clone(), fetcher).input(task.solution.day())
+        }
+    }
+}
+
+/// Checks that every `--strategy` names a strategy of a part that will run.
+/// A choice for a day that is not running is left alone.
+///
+/// Strategies from the config file are only defaults, so they just need to
+/// be a strategy of either part of their day, whichever parts run.
+pub fn check_strategies(args: &Args, solutions: &[&'static dyn DynSolution]) -> anyhow::Result<()> {
+    for choice in &args.strategies {
+        let mut solutions = solutions
+            .iter()
+            .filter(|solution| choice.applies_to(solution.day()))
+            .peekable();
+
+        if choice.day.is_some() && solutions.peek().is_none() {
+            continue;
+        }
+
+        let found = solutions
+            .flat_map(|solution| {
+                (1..=2)
+                    .filter(|part| choice.from_config || args.runs_part(*part))
+                    .flat_map(move |part| solution.strategies(part))
+            })
+            .any(|name| name == choice.name);
+
+        if !found {
+            match (choice.day, choice.from_config) {
+                (Some(day), true) => bail!(
+                    "Day {} has no strategy {}, set in {}",
+                    day,
+                    choice.name,
+                    config::CONFIG_FILE
+                ),
+                (Some(day), false) => bail!("Day {} has no strategy {}", day, choice.name),
+                (None, _) => bail!("No day has a strategy {}", choice.name),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn solve_part(
+    args: &Args,
+    workers: &Workers,
+    solution: &'static dyn DynSolution,
+    input: &ParsedInput,
+    part: u32,
+    strategy: &'static str,
+) -> (Result<Answer, SolveError>, Option<Stats>) {
+    let runs = args.runs();
+    let input = input.clone();
+
+    let others: Vec<_> = if args.cross_check {
+        solution
+            .strategies(part)
+            .into_iter()
+            .filter(|other| *other != strategy)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let result = isolate::run(args.timeout, workers, move || {
+        let (answer, time) = bench::run(runs, || solution.solve_with(part, strategy, &input));
+        let answer = answer?;
+
+        for other in others {
+            let other_answer = solution.solve_with(part, other, &input)?;
+
+            if other_answer != answer {
+                bail!(
+                    "Strategies disagree: {} gave {}, {} gave {}",
+                    strategy,
+                    answer,
+                    other,
+                    other_answer
+                );
+            }
+        }
+
+        Ok((answer, time))
+    });
+
+    match result {
+        Ok((answer, time)) => (Ok(answer), Some(time)),
+        Err(err) => (Err(err), None),
+    }
+}
+
+/// Solves each of `parts`, a part and the strategy to solve it with.
+fn solve_parts(
+    args: &Args,
+    workers: &Workers,
+    solution: &'static dyn DynSolution,
+    input: &ParsedInput,
+    parts: &[(u32, &'static str)],
+) -> Vec<(Result<Answer, SolveError>, Option<Stats>)> {
+    if args.jobs > 1 {
+        thread::scope(|scope| {
+            let handles: Vec<_> = parts
+                .iter()
+                .map(|&(part, strategy)| {
+                    scope.spawn(move || solve_part(args, workers, solution, input, part, strategy))
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Part thread panicked"))
+                .collect()
+        })
+    } else {
+        parts
+            .iter()
+            .map(|&(part, strategy)| solve_part(args, workers, solution, input, part, strategy))
+            .collect()
+    }
+}
+
+/// Runs the requested parts of a day, each on its own thread so that a panic
+/// or a timeout only fails that part. With more than one job the parts run at
+/// the same time.
+///
+/// An example only runs the parts its answers file has answers for, as some
+/// examples only apply to one part.
+///
+/// Parts with a cached answer skip solving, and if every part has one the
+/// input is not even parsed.
+pub fn run_day(args: &Args, task: &Task) -> DayReport {
+    let solution = task.solution;
+
+    let mut report = DayReport {
+        day: solution.day(),
+        name: solution.name(),
+        example: task.example.as_ref().map(|path| {
+            path.file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        }),
+        requested_parts: (1..=2).filter(|part| args.runs_part(*part)).collect(),
+        parse_time: None,
+        rejected: Vec::new(),
+        failure: None,
+        parts: Vec::new(),
+    };
+
+    let path = task.input_path(args);
+
+    let input = match read_input(args, task) {
+        Ok(input) => input,
+        Err(err) => {
+            report.failure = Some(Failure::Input(format!("{}: {}", path.display(), err)));
+            return report;
+        }
+    };
+
+    let answers_path = path.with_extension("answers");
+
+    // Input piped through stdin has no answers file to go with it.
+    let answers = if path == Path::new("-") {
+        Ok(None)
+    } else {
+        Answers::load(&answers_path)
+    };
+
+    let answers = match answers {
+        Ok(answers) => answers.unwrap_or_default(),
+        Err(err) => {
+            let err = format!("{}: {}", answers_path.display(), err);
+            report.failure = Some(Failure::Answers(err));
+            return report;
+        }
+    };
+
+    if task.example.is_some() && !answers.is_empty() {
+        report
+            .requested_parts
+            .retain(|&part| answers.get(part).is_some());
+    }
+
+    let parts: Vec<_> = report
+        .requested_parts
+        .iter()
+        .map(|&part| {
+            (
+                part,
+                args.strategy(solution.day(), &solution.strategies(part)),
+            )
+        })
+        .collect();
+
+    let cache = args.cache();
+    let input_hash = cache::fnv1a(input.as_bytes());
+
+    let key = |part, strategy| cache::Key {
+        day: solution.day(),
+        part,
+        strategy,
+        input: input_hash,
+    };
+
+    let mut results: Vec<_> = parts
+        .iter()
+        .map(|&(part, strategy)| {
+            let answer = cache.as_ref()?.get(key(part, strategy))?;
+
+            Some((Ok(answer), None, true))
+        })
+        .collect();
+
+    let unsolved: Vec<_> = parts
+        .iter()
+        .zip(&results)
+        .filter(|(_, result)| result.is_none())
+        .map(|(part, _)| *part)
+        .collect();
+
+    if !unsolved.is_empty() {
+        let runs = args.runs();
+        let lenient = args.lenient;
+
+        let parsed = isolate::run(args.timeout, &task.workers, move || {
+            let input = if lenient {
+                PuzzleInput::lenient(&input)
+            } else {
+                PuzzleInput::new(&input)
+            };
+
+            // Only keep what the last run rejected, as every run rejects the
+            // same.
+            let mut rejected = Vec::new();
+
+            let (parsed, time) = bench::run(runs, || {
+                let parsed = solution.parse(&input);
+                rejected = input.take_rejected();
+                parsed
+            });
+
+            parsed.map(|parsed| (parsed, time, rejected))
+        });
+
+        let input = match parsed {
+            Ok((input, parse_time, rejected)) => {
+                report.parse_time = Some(parse_time);
+                report.rejected = rejected;
+                input
+            }
+            Err(err) => {
+                report.failure = Some(Failure::Parse(err.to_string()));
+                return report;
+            }
+        };
+
+        let mut solved = solve_parts(args, &task.workers, solution, &input, &unsolved).into_iter();
+
+        for (&(part, strategy), result) in parts.iter().zip(&mut results) {
+            if result.is_none() {
+                let (answer, time) = solved.next().expect("Missing solved part");
+
+                // A cache that can't be written to only costs time later.
+                if let (Some(cache), Ok(answer)) = (&cache, &answer) {
+                    cache.put(key(part, strategy), answer).ok();
+                }
+
+                *result = Some((answer, time, false));
+            }
+        }
+    }
+
+    for ((part, strategy), result) in parts.into_iter().zip(results) {
+        let (result, time, cached) = result.expect("Unsolved part");
+
+        let verdict = result
+            .as_ref()
+            .ok()
+            .map(|answer| Verdict::check(answers.get(part), answer));
+
+        report.parts.push(PartReport {
+            part,
+            strategy,
+            result,
+            verdict,
+            time,
+            cached,
+        });
+    }
+
+    report
+}
+
+/// Runs each task on a pool of `args.jobs` threads, handing the reports to
+/// `on_report` in the same order as `tasks` regardless of which one finishes
+/// first.
+pub fn run_days<F>(args: &Args, tasks: &[Task], mut on_report: F)
+where
+    F: FnMut(DayReport),
+{
+    if args.jobs <= 1 {
+        for task in tasks {
+            on_report(run_day(args, task));
+        }
+
+        return;
+    }
+
+    let next_day = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+
+        for _ in 0..args.jobs.min(tasks.len()) {
+            let sender = sender.clone();
+            let next_day = &next_day;
+
+            scope.spawn(move || loop {
+                let i = next_day.fetch_add(1, Ordering::Relaxed);
+
+                match tasks.get(i) {
+                    Some(task) => {
+                        if sender.send((i, run_day(args, task))).is_err() {
+                            return;
+                        }
+                    }
+                    None => return,
+                }
+            });
+        }
+
+        drop(sender);
+
+        let mut finished = BTreeMap::new();
+        let mut next_to_report = 0;
+
+        for (i, report) in receiver {
+            finished.insert(i, report);
+
+            while let Some(report) = finished.remove(&next_to_report) {
+                on_report(report);
+                next_to_report += 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    fn part(result: Result<i32, &str>, verdict: Option<Verdict>) -> PartReport {
+        PartReport {
+            part: 1,
+            strategy: "default",
+            result: result
+                .map(Answer::from)
+                .map_err(|err| SolveError::Error(String::from(err))),
+            verdict,
+            time: Some(Stats::from_samples(vec![Duration::from_millis(1)])),
+            cached: false,
+        }
+    }
+
+    fn report(parts: Vec<PartReport>) -> DayReport {
+        DayReport {
+            day: 1,
+            name: "Report Repair",
+            example: None,
+            requested_parts: vec![1],
+            parse_time: None,
+            rejected: Vec::new(),
+            failure: None,
+            parts,
+        }
+    }
+
+    #[test]
+    fn test_passed() {
+        assert!(report(vec![part(Ok(1), Some(Verdict::Pass))]).passed());
+        assert!(report(vec![part(Ok(1), Some(Verdict::Unknown))]).passed());
+
+        let fail = Verdict::Fail {
+            expected: String::from("2"),
+        };
+        assert!(!report(vec![part(Ok(1), Some(fail))]).passed());
+        assert!(!report(vec![part(Err("oops"), None)]).passed());
+
+        let mut failed = report(vec![]);
+        failed.failure = Some(Failure::Input(String::from("missing")));
+        assert!(!failed.passed());
+    }
+
+    #[test]
+    fn test_run_days_in_order() {
+        use advent_of_code_2020_rs::solution::SOLUTIONS;
+
+        let input_dir = std::env::temp_dir().join(format!("aoc-run-days-{}", std::process::id()));
+        fs::create_dir_all(&input_dir).unwrap();
+        fs::write(input_dir.join("day1"), "1721\n979\n366\n299\n675\n1456\n").unwrap();
+        fs::write(input_dir.join("day6"), "abc\n\na\nb\nc\n").unwrap();
+
+        let args = Args {
+            input_dir: input_dir.clone(),
+            jobs: 4,
+            ..Args::default()
+        };
+
+        let mut days = Vec::new();
+        run_days(&args, &tasks(&args, &SOLUTIONS[..8]), |report| {
+            days.push((report.day, report.passed()))
+        });
+
+        fs::remove_dir_all(&input_dir).unwrap();
+
+        let expected: Vec<_> = (1..=8).map(|day| (day, day == 1 || day == 6)).collect();
+        assert_eq!(days, expected);
+    }
+
+    #[test]
+    fn test_run_day_lenient() {
+        use advent_of_code_2020_rs::solution::find;
+
+        let input = std::env::temp_dir().join(format!("aoc-lenient-{}", std::process::id()));
+        fs::write(&input, "1721\n979\n36x6\n366\n299\n675\n\n1456\n").unwrap();
+
+        let task = Task {
+            solution: find(1).unwrap(),
+            example: None,
+            workers: Workers::default(),
+        };
+
+        let mut args = Args {
+            input: Some(input.clone()),
+            ..Args::default()
+        };
+
+        let strict = run_day(&args, &task);
+
+        args.lenient = true;
+        let lenient = run_day(&args, &task);
+
+        fs::remove_file(&input).unwrap();
+
+        assert!(matches!(strict.failure, Some(Failure::Parse(_))));
+
+        assert!(lenient.passed());
+        assert_eq!(lenient.parts[0].result, Ok(Answer::Integer(514579)));
+
+        let lines: Vec<_> = lenient.rejected.iter().map(|err| err.line).collect();
+        assert_eq!(lines, vec![3, 7]);
+    }
+
+    #[test]
+    fn test_run_day_cached() {
+        use advent_of_code_2020_rs::solution::find;
+
+        let dir = std::env::temp_dir().join(format!("aoc-cached-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("day1"), "1721\n979\n366\n299\n675\n1456\n").unwrap();
+
+        let task = Task {
+            solution: find(1).unwrap(),
+            example: None,
+            workers: Workers::default(),
+        };
+
+        let mut args = Args {
+            input_dir: dir.clone(),
+            cache_dir: dir.join("cache"),
+            build: Some(1),
+            ..Args::default()
+        };
+
+        let cached = |args: &Args| {
+            let report = run_day(args, &task);
+            assert!(report.passed(), "{:?}", report);
+
+            report
+                .parts
+                .iter()
+                .map(|part| part.cached)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(cached(&args), vec![false, false]);
+        assert_eq!(cached(&args), vec![true, true]);
+
+        args.part = Some(2);
+        args.strategies = vec![crate::args::StrategyChoice {
+            day: None,
+            name: String::from("hash-set"),
+            from_config: false,
+        }];
+        assert_eq!(cached(&args), vec![false]);
+        assert_eq!(cached(&args), vec![true]);
+
+        args.build = Some(2);
+        assert_eq!(cached(&args), vec![false]);
+
+        args.no_cache = true;
+        assert_eq!(cached(&args), vec![false]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cross_check() {
+        use crate::args::StrategyChoice;
+        use advent_of_code_2020_rs::solution::SOLUTIONS;
+
+        let mut args = Args {
+            example: true,
+            cross_check: true,
+            strategies: vec![StrategyChoice {
+                day: None,
+                name: String::from("hash-set"),
+                from_config: false,
+            }],
+            ..Args::default()
+        };
+
+        let mut strategies = Vec::new();
+        run_days(&args, &tasks(&args, &SOLUTIONS[..1]), |report| {
+            assert!(report.passed(), "{:?}", report);
+            strategies.extend(report.parts.iter().map(|part| part.strategy));
+        });
+
+        assert_eq!(strategies, vec!["hash-set", "hash-set"]);
+
+        assert!(check_strategies(&args, SOLUTIONS).is_ok());
+
+        args.strategies[0].day = Some(2);
+        assert!(check_strategies(&args, SOLUTIONS).is_err());
+        assert!(check_strategies(&args, &SOLUTIONS[..1]).is_ok());
+    }
+
+    #[test]
+    fn test_config_strategies() {
+        use crate::config::Config;
+        use advent_of_code_2020_rs::solution::SOLUTIONS;
+
+        let config: Config = "[strategies]\n8 = \"in-place\"".parse().unwrap();
+
+        let parse = |flags: &[&str]| {
+            let flags = flags.iter().map(|s| s.to_string());
+            let args = Args::parse(config.defaults(), flags).unwrap();
+
+            (check_strategies(&args, SOLUTIONS), args)
+        };
+
+        // Day 8 only has an in-place strategy for part 2, which part 1 runs
+        // without.
+        let (checked, args) = parse(&["--example", "--day", "8", "--part", "1"]);
+        assert!(checked.is_ok());
+
+        let mut strategies = Vec::new();
+        run_days(&args, &tasks(&args, &SOLUTIONS[7..8]), |report| {
+            assert!(report.passed(), "{:?}", report);
+            strategies.extend(report.parts.iter().map(|part| part.strategy));
+        });
+        assert_eq!(strategies, vec!["default"]);
+
+        assert!(parse(&["--days", "1-8", "--part", "1"]).0.is_ok());
+        assert!(parse(&["--day", "8"]).0.is_ok());
+
+        // The same choice on the command line still has to apply.
+        assert!(parse(&["--day", "8", "--part", "1", "-s", "8:in-place"])
+            .0
+            .is_err());
+
+        let config: Config = "[strategies]\n8 = \"nope\"".parse().unwrap();
+        let args = Args::parse(config.defaults(), vec![String::from("--part=1")]).unwrap();
+        assert!(check_strategies(&args, SOLUTIONS).is_err());
+    }
+
+    #[test]
+    fn test_examples_pass() {
+        use advent_of_code_2020_rs::solution::SOLUTIONS;
+
+        let args = Args {
+            example: true,
+            ..Args::default()
+        };
+
+        // Only the solved days, so a day fresh from `new-day` does not fail
+        // this before it has been written.
+        let mut examples = Vec::new();
+        run_days(&args, &tasks(&args, &SOLUTIONS[..8]), |report| {
+            assert!(report.passed(), "{:?}", report);
+            assert!(!report.parts.is_empty());
+            examples.push((report.day, report.example.unwrap()));
+        });
+
+        assert_eq!(examples.len(), 11);
+        assert_eq!(examples[3], (4, String::from("1")));
+        assert_eq!(examples[5], (4, String::from("3")));
+    }
+}
@@ -0,0 +1,356 @@
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context as _};
+
+/// The module written for a new day. `{day}` is filled in, and `{name}` with
+/// a string literal.
+const MODULE_TEMPLATE: &str = r#"use crate::input::PuzzleInput;
+use crate::parse::ParseError;
+use crate::solution::Solution;
+
+pub fn parse_input(input: &PuzzleInput) -> Result<Vec<String>, ParseError> {
+    Ok(input.lines().map(String::from).collect())
+}
+
+pub fn part1(_input: &[String]) -> anyhow::Result<usize> {
+    Err(anyhow::anyhow!("Part 1 is not solved yet"))
+}
+
+pub fn part2(_input: &[String]) -> anyhow::Result<usize> {
+    Err(anyhow::anyhow!("Part 2 is not solved yet"))
+}
+
+pub struct Day{day};
+
+impl Solution for Day{day} {
+    const DAY: u32 = {day};
+    const NAME: &'static str = {name};
+
+    type Input = Vec<String>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &PuzzleInput) -> anyhow::Result<Self::Input> {
+        Ok(parse_input(input)?)
+    }
+
+    fn part1(input: &Self::Input) -> anyhow::Result<Self::Output1> {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> anyhow::Result<Self::Output2> {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() {
+        let input = PuzzleInput::new(include_str!("../examples/day{day}/1.txt"));
+
+        let input = parse_input(&input).unwrap();
+
+        assert!(input.is_empty());
+    }
+}
+"#;
+
+/// The line width rustfmt wraps at.
+const MAX_WIDTH: usize = 100;
+
+const ANSWERS_TEMPLATE: &str = "\
+# Expected answers for this example, one `<part>: <answer>` line per part.
+# 1:
+# 2:
+";
+
+fn render_module(day: u32, name: &str) -> String {
+    MODULE_TEMPLATE
+        .replace("{day}", &day.to_string())
+        .replace("{name}", &format!("{:?}", name))
+}
+
+/// Inserts `line` among the lines of `source` that `key` gives a day number,
+/// keeping them sorted by day. Fails if there are no such lines to anchor on.
+fn insert_sorted<F>(source: &str, day: u32, line: &str, key: F) -> anyhow::Result<String>
+where
+    F: Fn(&str) -> Option<u32>,
+{
+    let lines: Vec<&str> = source.lines().collect();
+
+    let days: Vec<(usize, u32)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| key(line).map(|day| (i, day)))
+        .collect();
+
+    let last = days
+        .last()
+        .ok_or_else(|| anyhow!("Could not find where to add day {}", day))?;
+
+    let position = days
+        .iter()
+        .find(|(_, existing)| *existing > day)
+        .map_or(last.0 + 1, |(i, _)| *i);
+
+    let mut lines = lines;
+    lines.insert(position, line);
+
+    Ok(lines.join("\n") + "\n")
+}
+
+fn day_number(s: &str, prefix: &str, suffix: &str) -> Option<u32> {
+    s.strip_prefix(prefix)?.strip_suffix(suffix)?.parse().ok()
+}
+
+/// Adds `pub mod dayN;` to the library root.
+fn add_module(lib: &str, day: u32) -> anyhow::Result<String> {
+    let line = format!("pub mod day{};", day);
+
+    if lib.lines().any(|existing| existing == line) {
+        bail!("Day {} already exists", day);
+    }
+
+    insert_sorted(lib, day, &line, |line| day_number(line, "pub mod day", ";"))
+}
+
+/// Finds the `use crate::{dayN, ...};` import, on one line or wrapped over
+/// several, returning where it is and the days it imports.
+fn day_imports(solution: &str) -> Option<(Range<usize>, Vec<u32>)> {
+    solution
+        .match_indices("use crate::{")
+        .find_map(|(start, prefix)| {
+            let body = start + prefix.len();
+            let end = body + solution[body..].find("};")?;
+
+            let days = solution[body..end]
+                .split(',')
+                .map(str::trim)
+                .filter(|module| !module.is_empty())
+                .map(|module| day_number(module, "day", ""))
+                .collect::<Option<Vec<_>>>()?;
+
+            if days.is_empty() {
+                return None;
+            }
+
+            Some((start..(end + 2), days))
+        })
+}
+
+/// Writes the import of `days`, wrapping it the way rustfmt does once it no
+/// longer fits on one line.
+fn format_imports(days: &[u32]) -> String {
+    let modules: Vec<String> = days.iter().map(|day| format!("day{}", day)).collect();
+    let line = format!("use crate::{{{}}};", modules.join(", "));
+
+    if line.len() <= MAX_WIDTH {
+        return line;
+    }
+
+    let mut lines = vec![String::from("use crate::{")];
+    let mut current = String::new();
+
+    for module in modules {
+        if !current.is_empty() && current.len() + module.len() + 2 > MAX_WIDTH {
+            lines.push(current);
+            current = String::new();
+        }
+
+        current.push_str(if current.is_empty() { "    " } else { " " });
+        current.push_str(&module);
+        current.push(',');
+    }
+
+    lines.push(current);
+    lines.push(String::from("};"));
+
+    lines.join("\n")
+}
+
+/// Adds day `N` to the imports and the `SOLUTIONS` registry.
+fn register(solution: &str, day: u32) -> anyhow::Result<String> {
+    let (span, mut days) =
+        day_imports(solution).ok_or_else(|| anyhow!("Could not find the day imports"))?;
+
+    days.push(day);
+    days.sort_unstable();
+
+    let solution = format!(
+        "{}{}{}",
+        &solution[..span.start],
+        format_imports(&days),
+        &solution[span.end..]
+    );
+
+    let entry = format!("    &day{}::Day{},", day, day);
+
+    insert_sorted(&solution, day, &entry, |line| {
+        let module = line.strip_prefix("    &day")?;
+        module[..module.find("::")?].parse().ok()
+    })
+}
+
+fn create(path: &Path, contents: &str, created: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    if path.exists() {
+        return Ok(());
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, contents).with_context(|| format!("Could not write {}", path.display()))?;
+    created.push(path.to_path_buf());
+
+    Ok(())
+}
+
+/// Creates `src/dayN.rs`, registers it with the runner and adds empty input
+/// and example files, returning every file it created or changed. Must be
+/// run from the root of the repository.
+pub fn new_day(input_dir: &Path, day: u32, name: Option<&str>) -> anyhow::Result<Vec<PathBuf>> {
+    if !(1..=25).contains(&day) {
+        bail!("Day must be between 1 and 25, got {}", day);
+    }
+
+    let lib_path = Path::new("src/lib.rs");
+    let solution_path = Path::new("src/solution.rs");
+    let module_path = PathBuf::from(format!("src/day{}.rs", day));
+
+    if module_path.exists() {
+        bail!("{} already exists", module_path.display());
+    }
+
+    let lib = fs::read_to_string(lib_path)
+        .context("Could not read src/lib.rs; run new-day from the repository root")?;
+    let solution = fs::read_to_string(solution_path)?;
+
+    // Work out both edits before touching anything, so a failure leaves the
+    // tree as it was.
+    let lib = add_module(&lib, day)?;
+    let solution = register(&solution, day)?;
+
+    let mut created = Vec::new();
+
+    let name = name.map_or_else(|| format!("Day {}", day), String::from);
+    create(&module_path, &render_module(day, &name), &mut created)?;
+
+    fs::write(lib_path, lib)?;
+    fs::write(solution_path, solution)?;
+    created.push(lib_path.to_path_buf());
+    created.push(solution_path.to_path_buf());
+
+    let examples = PathBuf::from(format!("examples/day{}", day));
+    create(&examples.join("1.txt"), "", &mut created)?;
+    create(&examples.join("1.answers"), ANSWERS_TEMPLATE, &mut created)?;
+    create(&input_dir.join(format!("day{}", day)), "", &mut created)?;
+
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    #[test]
+    fn test_add_module() {
+        let lib = indoc! {"
+            //! Solutions.
+
+            pub mod day1;
+            pub mod day2;
+            pub mod day10;
+            pub mod solution;
+        "};
+
+        assert_eq!(
+            add_module(lib, 3).unwrap(),
+            indoc! {"
+                //! Solutions.
+
+                pub mod day1;
+                pub mod day2;
+                pub mod day3;
+                pub mod day10;
+                pub mod solution;
+            "}
+        );
+        assert!(add_module(lib, 2).is_err());
+    }
+
+    #[test]
+    fn test_register() {
+        let solution = indoc! {"
+            use crate::{day1, day2};
+
+            pub const SOLUTIONS: &[&dyn DynSolution] = &[
+                &day1::Day1,
+                &day2::Day2,
+            ];
+        "};
+
+        assert_eq!(
+            register(solution, 9).unwrap(),
+            indoc! {"
+                use crate::{day1, day2, day9};
+
+                pub const SOLUTIONS: &[&dyn DynSolution] = &[
+                    &day1::Day1,
+                    &day2::Day2,
+                    &day9::Day9,
+                ];
+            "}
+        );
+    }
+
+    #[test]
+    fn test_register_wrapped() {
+        let mut solution = String::from(include_str!("solution.rs"));
+
+        for day in 9..=25 {
+            solution = register(&solution, day).unwrap();
+
+            assert!(solution.lines().all(|line| line.len() <= MAX_WIDTH));
+        }
+
+        let (_, days) = day_imports(&solution).unwrap();
+        assert_eq!(days, (1..=25).collect::<Vec<_>>());
+
+        let wrapped = indoc! {"
+            use crate::{
+                day1, day2,
+                day3,
+            };
+
+            pub const SOLUTIONS: &[&dyn DynSolution] = &[
+                &day1::Day1,
+            ];
+        "};
+
+        assert!(register(wrapped, 4)
+            .unwrap()
+            .starts_with("use crate::{day1, day2, day3, day4};\n"));
+    }
+
+    #[test]
+    fn test_render_module() {
+        let module = render_module(9, "Encoding Error");
+
+        assert!(module.contains("pub struct Day9;"));
+        assert!(module.contains("const NAME: &'static str = \"Encoding Error\";"));
+        assert!(module.contains("include_str!(\"../examples/day9/1.txt\")"));
+        assert!(!module.contains("{day}"));
+
+        let module = render_module(9, r#"A "quoted" \ name"#);
+
+        assert!(module.contains(r#"const NAME: &'static str = "A \"quoted\" \\ name";"#));
+    }
+}
@@ -0,0 +1,236 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+
+use crate::answer::Answer;
+use crate::input::PuzzleInput;
+use crate::{day1, day2, day3, day4, day5, day6, day7, day8};
+
+/// Every day with a solution, in the order the runner visits them.
+pub const SOLUTIONS: &[&dyn DynSolution] = &[
+    &day1::Day1,
+    &day2::Day2,
+    &day3::Day3,
+    &day4::Day4,
+    &day5::Day5,
+    &day6::Day6,
+    &day7::Day7,
+    &day8::Day8,
+];
+
+/// The name of the strategy that runs [`Solution::part1`] or
+/// [`Solution::part2`] themselves.
+pub const DEFAULT_STRATEGY: &str = "default";
+
+/// Another way to solve a part, which can be picked by name instead of the
+/// default one.
+pub struct Strategy<I, O> {
+    pub name: &'static str,
+    pub solve: fn(&I) -> anyhow::Result<O>,
+}
+
+/// A single day's puzzle.
+///
+/// The raw input is parsed once into `Input`, which is then shared by both
+/// parts. Each part's output is turned into an [`Answer`].
+pub trait Solution {
+    /// The day of the advent calendar, starting at 1.
+    const DAY: u32;
+
+    /// The title of the puzzle.
+    const NAME: &'static str;
+
+    type Input: Send + Sync + 'static;
+    type Output1: Into<Answer> + 'static;
+    type Output2: Into<Answer> + 'static;
+
+    /// Other ways to solve part 1, alongside [`Solution::part1`] itself.
+    const STRATEGIES1: &'static [Strategy<Self::Input, Self::Output1>] = &[];
+
+    /// Other ways to solve part 2, alongside [`Solution::part2`] itself.
+    const STRATEGIES2: &'static [Strategy<Self::Input, Self::Output2>] = &[];
+
+    fn parse(input: &PuzzleInput) -> anyhow::Result<Self::Input>;
+
+    fn part1(input: &Self::Input) -> anyhow::Result<Self::Output1>;
+
+    fn part2(input: &Self::Input) -> anyhow::Result<Self::Output2>;
+}
+
+/// Solves a part with the strategy called `name`, where `default` is the
+/// part's own solver.
+fn solve_with<I, O: Into<Answer>>(
+    default: fn(&I) -> anyhow::Result<O>,
+    strategies: &[Strategy<I, O>],
+    name: &str,
+    input: &I,
+) -> Option<anyhow::Result<Answer>> {
+    let solve = if name == DEFAULT_STRATEGY {
+        default
+    } else {
+        strategies
+            .iter()
+            .find(|strategy| strategy.name == name)?
+            .solve
+    };
+
+    Some(solve(input).map(Into::into))
+}
+
+/// Parsed input with its type erased, so days can be stored side by side.
+///
+/// It is reference counted so each part can be handed its own copy to run on
+/// a separate thread.
+pub type ParsedInput = Arc<dyn Any + Send + Sync>;
+
+/// The object safe view of a [`Solution`] that the runner works with.
+pub trait DynSolution: Sync {
+    fn day(&self) -> u32;
+
+    fn name(&self) -> &'static str;
+
+    fn parse(&self, input: &PuzzleInput) -> anyhow::Result<ParsedInput>;
+
+    /// The names of the strategies for `part`, starting with the default.
+    fn strategies(&self, part: u32) -> Vec<&'static str>;
+
+    /// Solves `part` with the strategy called `strategy`.
+    fn solve_with(&self, part: u32, strategy: &str, input: &ParsedInput) -> anyhow::Result<Answer>;
+
+    fn solve(&self, part: u32, input: &ParsedInput) -> anyhow::Result<Answer> {
+        self.solve_with(part, DEFAULT_STRATEGY, input)
+    }
+}
+
+impl<S: Solution + Sync> DynSolution for S {
+    fn day(&self) -> u32 {
+        S::DAY
+    }
+
+    fn name(&self) -> &'static str {
+        S::NAME
+    }
+
+    fn parse(&self, input: &PuzzleInput) -> anyhow::Result<ParsedInput> {
+        Ok(Arc::new(S::parse(input)?))
+    }
+
+    fn strategies(&self, part: u32) -> Vec<&'static str> {
+        let others: Vec<_> = match part {
+            1 => S::STRATEGIES1
+                .iter()
+                .map(|strategy| strategy.name)
+                .collect(),
+            2 => S::STRATEGIES2
+                .iter()
+                .map(|strategy| strategy.name)
+                .collect(),
+            _ => return Vec::new(),
+        };
+
+        let mut names = vec![DEFAULT_STRATEGY];
+        names.extend(others);
+        names
+    }
+
+    fn solve_with(&self, part: u32, strategy: &str, input: &ParsedInput) -> anyhow::Result<Answer> {
+        let input = input
+            .downcast_ref::<S::Input>()
+            .ok_or_else(|| anyhow!("Input was not parsed by day {}", S::DAY))?;
+
+        let answer = match part {
+            1 => solve_with(S::part1, S::STRATEGIES1, strategy, input),
+            2 => solve_with(S::part2, S::STRATEGIES2, strategy, input),
+            _ => return Err(anyhow!("Day {} has no part {}", S::DAY, part)),
+        };
+
+        answer.unwrap_or_else(|| {
+            Err(anyhow!(
+                "Day {} part {} has no strategy {}",
+                S::DAY,
+                part,
+                strategy
+            ))
+        })
+    }
+}
+
+pub fn find(day: u32) -> Option<&'static dyn DynSolution> {
+    SOLUTIONS
+        .iter()
+        .copied()
+        .find(|solution| solution.day() == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_is_ordered_and_unique() {
+        let days: Vec<u32> = SOLUTIONS.iter().map(|solution| solution.day()).collect();
+
+        assert!(days.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(days[..8], [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(
+            find(7).map(|solution| solution.name()),
+            Some("Handy Haversacks")
+        );
+        assert!(find(0).is_none());
+        assert!(find(26).is_none());
+    }
+
+    #[test]
+    fn test_solve_with_wrong_input() {
+        let input: ParsedInput = Arc::new(String::from("not a tree map"));
+
+        assert!(find(3).unwrap().solve(1, &input).is_err());
+    }
+
+    #[test]
+    fn test_solve_shares_parsed_input() {
+        let solution = find(1).unwrap();
+        let input = PuzzleInput::new("1721\n979\n366\n299\n675\n1456\n");
+        let input = solution.parse(&input).unwrap();
+
+        assert_eq!(solution.solve(1, &input).unwrap(), Answer::Integer(514579));
+        assert_eq!(
+            solution.solve(2, &input).unwrap(),
+            Answer::Integer(241861950)
+        );
+        assert!(solution.solve(3, &input).is_err());
+    }
+
+    #[test]
+    fn test_strategies_agree() {
+        let inputs = [
+            (1, "1721\n979\n366\n299\n675\n1456\n"),
+            (7, include_str!("../examples/day7/1.txt")),
+            (8, include_str!("../examples/day8/1.txt")),
+        ];
+
+        for (day, input) in inputs.iter() {
+            let solution = find(*day).unwrap();
+            let input = solution.parse(&PuzzleInput::new(input)).unwrap();
+
+            for part in 1..=2 {
+                let strategies = solution.strategies(part);
+                let expected = solution.solve(part, &input).unwrap();
+
+                assert_eq!(strategies[0], DEFAULT_STRATEGY);
+
+                for strategy in strategies {
+                    let answer = solution.solve_with(part, strategy, &input).unwrap();
+                    assert_eq!(answer, expected, "day {} part {} {}", day, part, strategy);
+                }
+            }
+
+            assert!(solution.solve_with(1, "guesswork", &input).is_err());
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::args::Args;
+use crate::output::Printer;
+use crate::runner::{self, DayReport, Task};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Enough of a file's metadata to notice that it was edited, created or
+/// removed.
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// The files each task depends on: its input and its answers.
+fn watched_paths(args: &Args, task: &Task) -> Vec<PathBuf> {
+    let input = task.input_path(args);
+    let answers = input.with_extension("answers");
+
+    vec![input, answers]
+}
+
+/// Tracks the files of a set of days and reports which ones changed.
+struct Watcher {
+    stamps: BTreeMap<PathBuf, (u32, Stamp)>,
+}
+
+impl Watcher {
+    fn new(args: &Args, tasks: &[Task]) -> Self {
+        let stamps = tasks
+            .iter()
+            .flat_map(|task| {
+                let day = task.solution.day();
+
+                watched_paths(args, task)
+                    .into_iter()
+                    .map(move |path| (path, day))
+            })
+            .map(|(path, day)| {
+                let stamp = stamp(&path);
+                (path, (day, stamp))
+            })
+            .collect();
+
+        Self { stamps }
+    }
+
+    /// Returns the paths that changed since the last poll, with their day.
+    fn poll(&mut self) -> Vec<(u32, PathBuf)> {
+        let mut changed = Vec::new();
+
+        for (path, (day, old_stamp)) in self.stamps.iter_mut() {
+            let new_stamp = stamp(path);
+
+            if new_stamp != *old_stamp {
+                *old_stamp = new_stamp;
+                changed.push((*day, path.clone()));
+            }
+        }
+
+        changed
+    }
+}
+
+/// A one line summary of each part's result, used to spot what changed
+/// between runs.
+fn outcomes(report: &DayReport) -> BTreeMap<u32, String> {
+    if let Some(failure) = &report.failure {
+        return report
+            .requested_parts
+            .iter()
+            .map(|&part| (part, failure.to_string()))
+            .collect();
+    }
+
+    report
+        .parts
+        .iter()
+        .map(|part| {
+            let outcome = match (&part.result, &part.verdict) {
+                (Ok(answer), Some(verdict)) => format!("{} ({})", answer, verdict.as_str()),
+                (Ok(answer), None) => answer.to_string(),
+                (Err(err), _) => format!("{}: {}", err.status(), err),
+            };
+
+            (part.part, outcome)
+        })
+        .collect()
+}
+
+fn changes(previous: &BTreeMap<u32, String>, current: &BTreeMap<u32, String>) -> Vec<String> {
+    current
+        .iter()
+        .filter_map(|(part, outcome)| match previous.get(part) {
+            Some(old) if old == outcome => None,
+            Some(old) => Some(format!("Part {}: {} -> {}", part, old, outcome)),
+            None => Some(format!("Part {}: {}", part, outcome)),
+        })
+        .collect()
+}
+
+/// Names the day and, when running examples, which example a report is for.
+fn label(report: &DayReport) -> String {
+    match &report.example {
+        Some(example) => format!("Day {}, example {}", report.day, example),
+        None => format!("Day {}", report.day),
+    }
+}
+
+/// Whether any task of `day` still has threads running, which can only be
+/// parts that timed out.
+fn busy(tasks: &[Task], day: u32) -> bool {
+    tasks
+        .iter()
+        .any(|task| task.solution.day() == day && task.workers.running() > 0)
+}
+
+/// Runs every task once, then re-runs a day whenever one of its input or
+/// answers files changes. Never returns; stop it with Ctrl-C.
+///
+/// A part that timed out can't be stopped, so a day is not re-run until its
+/// last run's threads have finished, rather than piling up more of them.
+pub fn run(args: &Args, tasks: &[Task], printer: &Printer) -> ! {
+    let mut watcher = Watcher::new(args, tasks);
+    let mut previous = BTreeMap::new();
+    let mut pending = BTreeSet::new();
+
+    runner::run_days(args, tasks, |report| {
+        printer.print(&report);
+        previous.insert(label(&report), outcomes(&report));
+    });
+
+    printer.print_status("Watching for changes...");
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let changed = watcher.poll();
+
+        for (day, path) in &changed {
+            printer.print_status(&format!("Changed: {}", path.display()));
+            pending.insert(*day);
+        }
+
+        let (waiting, ready): (Vec<u32>, Vec<u32>) =
+            pending.iter().partition(|&&day| busy(tasks, day));
+
+        for day in waiting {
+            if changed.iter().any(|(changed_day, _)| *changed_day == day) {
+                printer.print_status(&format!(
+                    "Day {}: waiting for a part that timed out to finish",
+                    day
+                ));
+            }
+        }
+
+        if ready.is_empty() {
+            continue;
+        }
+
+        for day in &ready {
+            pending.remove(day);
+        }
+
+        let changed_tasks: Vec<_> = tasks
+            .iter()
+            .filter(|task| ready.contains(&task.solution.day()))
+            .cloned()
+            .collect();
+
+        runner::run_days(args, &changed_tasks, |report| {
+            printer.print(&report);
+
+            let label = label(&report);
+            let current = outcomes(&report);
+
+            let lines = match previous.get(&label) {
+                Some(old) => changes(old, &current),
+                None => Vec::new(),
+            };
+
+            if lines.is_empty() {
+                printer.print_status(&format!("{}: no change", label));
+            } else {
+                for line in lines {
+                    printer.print_status(&format!("{}: {}", label, line));
+                }
+            }
+
+            previous.insert(label, current);
+        });
+
+        printer.print_status("Watching for changes...");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes() {
+        let previous: BTreeMap<_, _> = vec![(1, String::from("4 (PASS)")), (2, String::from("32"))]
+            .into_iter()
+            .collect();
+        let current: BTreeMap<_, _> = vec![(1, String::from("4 (PASS)")), (2, String::from("33"))]
+            .into_iter()
+            .collect();
+
+        assert_eq!(changes(&previous, &current), vec!["Part 2: 32 -> 33"]);
+        assert!(changes(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_watcher_poll() {
+        let input_dir = std::env::temp_dir().join(format!("aoc-watch-{}", std::process::id()));
+        fs::create_dir_all(&input_dir).unwrap();
+        fs::write(input_dir.join("day3"), "..#\n").unwrap();
+
+        let args = Args {
+            input_dir: input_dir.clone(),
+            ..Args::default()
+        };
+
+        let tasks: Vec<_> = [3, 4]
+            .iter()
+            .map(|&day| Task {
+                solution: advent_of_code_2020_rs::solution::find(day).unwrap(),
+                example: None,
+                workers: Default::default(),
+            })
+            .collect();
+
+        let mut watcher = Watcher::new(&args, &tasks);
+
+        assert!(watcher.poll().is_empty());
+
+        // Creating a file is noticed even without a modification time change.
+        fs::write(input_dir.join("day4.answers"), "1: 2\n").unwrap();
+        fs::write(input_dir.join("day3"), "..#.\n").unwrap();
+
+        let changed = watcher.poll();
+
+        fs::remove_dir_all(&input_dir).unwrap();
+
+        assert_eq!(
+            changed,
+            vec![
+                (3, input_dir.join("day3")),
+                (4, input_dir.join("day4.answers"))
+            ]
+        );
+    }
+}